@@ -9,9 +9,23 @@ struct Rotation {
 
 impl Rotation {
     fn apply(&self, current: i32) -> i32 {
+        self.apply_mod(current, 100)
+    }
+
+    /// Clicks moved, positive toward higher numbers (L) and negative toward lower (R)
+    fn signed_distance(&self) -> i64 {
         match self.direction {
-            'L' => (current + self.distance).rem_euclid(100),
-            'R' => (current - self.distance).rem_euclid(100),
+            'L' => self.distance as i64,
+            'R' => -(self.distance as i64),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Rotate on a dial with `modulus` positions
+    fn apply_mod(&self, current: i32, modulus: i32) -> i32 {
+        match self.direction {
+            'L' => (current + self.distance).rem_euclid(modulus),
+            'R' => (current - self.distance).rem_euclid(modulus),
             _ => unreachable!(),
         }
     }
@@ -31,16 +45,21 @@ impl FromStr for Rotation {
 }
 
 fn count_zero_crossings(current: i32, direction: char, distance: i32) -> u64 {
+    count_zero_crossings_mod(current, direction, distance, 100)
+}
+
+/// `count_zero_crossings` on a dial with `modulus` positions
+fn count_zero_crossings_mod(current: i32, direction: char, distance: i32, modulus: i32) -> u64 {
     if distance == 0 || current == 0 {
-        return (distance / 100) as u64;
+        return (distance / modulus) as u64;
     }
 
     match direction {
         'L' => {
             // Moving left (toward higher numbers): cross 0 at 100-current, 200-current, ...
-            let first_zero = 100 - current;
+            let first_zero = modulus - current;
             if distance >= first_zero {
-                ((distance - first_zero) / 100 + 1) as u64
+                ((distance - first_zero) / modulus + 1) as u64
             } else {
                 0
             }
@@ -48,7 +67,7 @@ fn count_zero_crossings(current: i32, direction: char, distance: i32) -> u64 {
         'R' => {
             // Moving right (toward lower numbers): cross 0 at current, 100+current, ...
             if distance >= current {
-                ((distance - current) / 100 + 1) as u64
+                ((distance - current) / modulus + 1) as u64
             } else {
                 0
             }
@@ -57,20 +76,115 @@ fn count_zero_crossings(current: i32, direction: char, distance: i32) -> u64 {
     }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
+/// Number of rotations that pass through zero at least once
+#[allow(dead_code)]
+fn count_crossing_rotations(input: &str) -> u64 {
     let (_, count) = input
         .lines()
         .filter(|line| !line.is_empty())
         .filter_map(|line| line.parse::<Rotation>().ok())
         .fold((50, 0), |(dial, count), rotation| {
-            let new_dial = rotation.apply(dial);
-            let new_count = count + (new_dial == 0) as u32;
+            let crossings = count_zero_crossings(dial, rotation.direction, rotation.distance);
+            (rotation.apply(dial), count + (crossings > 0) as u64)
+        });
+
+    count
+}
+
+/// Number of full passes over the rotation list after which the dial first returns to
+/// its starting position, or None if it doesn't within `max_passes`
+#[allow(dead_code)]
+fn cycle_length(input: &str, max_passes: usize) -> Option<usize> {
+    let rotations: Vec<Rotation> = input
+        .lines()
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| line.parse::<Rotation>().ok())
+        .collect();
+
+    let start = 50;
+    let mut dial = start;
+
+    (1..=max_passes).find(|_| {
+        dial = rotations
+            .iter()
+            .fold(dial, |dial, rotation| rotation.apply(dial));
+        dial == start
+    })
+}
+
+/// Number of rotations that leave a dial with `modulus` positions on any of `targets`,
+/// or None if `modulus` is below 1
+fn count_landings_on(input: &str, targets: &[i32], modulus: i32) -> Option<u64> {
+    if modulus <= 0 {
+        return None;
+    }
+
+    let (_, count) = input
+        .lines()
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| line.parse::<Rotation>().ok())
+        .fold((50, 0), |(dial, count), rotation| {
+            let new_dial = rotation.apply_mod(dial, modulus);
+            let new_count = count + targets.contains(&new_dial) as u64;
             (new_dial, new_count)
         });
 
     Some(count)
 }
 
+/// Total signed displacement of the dial before wrapping, with L rotations positive and
+/// R rotations negative
+fn net_rotation(input: &str) -> i64 {
+    input
+        .lines()
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| line.parse::<Rotation>().ok())
+        .map(|rotation| rotation.signed_distance())
+        .sum()
+}
+
+/// Number of complete net turns around a dial with `modulus` positions, signed like
+/// `net_rotation` and rounded toward zero, or None if `modulus` is below 1
+#[allow(dead_code)]
+fn winding_number(input: &str, modulus: i32) -> Option<i64> {
+    if modulus <= 0 {
+        return None;
+    }
+
+    Some(net_rotation(input) / modulus as i64)
+}
+
+/// 1-based index of the rotation after which the dial (with `modulus` positions) has
+/// passed through zero at least `threshold` times in total, or None if it never does.
+/// A threshold of 0 is met before the first rotation, at index 0. The dial starts at 50,
+/// wrapped onto the dial, and a `modulus` below 1 is rejected with None.
+#[allow(dead_code)]
+fn first_turn_threshold(input: &str, threshold: u64, modulus: i32) -> Option<usize> {
+    if modulus <= 0 {
+        return None;
+    }
+    if threshold == 0 {
+        return Some(0);
+    }
+
+    let mut dial = 50i32.rem_euclid(modulus);
+    let mut total = 0;
+    input
+        .lines()
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| line.parse::<Rotation>().ok())
+        .position(|rotation| {
+            total += count_zero_crossings_mod(dial, rotation.direction, rotation.distance, modulus);
+            dial = rotation.apply_mod(dial, modulus);
+            total >= threshold
+        })
+        .map(|idx| idx + 1)
+}
+
+pub fn part_one(input: &str) -> Option<u32> {
+    count_landings_on(input, &[0], 100).map(|count| count as u32)
+}
+
 pub fn part_two(input: &str) -> Option<u64> {
     let (_, count) = input
         .lines()
@@ -100,4 +214,63 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(6));
     }
+
+    #[test]
+    fn test_count_crossing_rotations() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        assert_eq!(count_crossing_rotations(&input), 6);
+
+        // A long final rotation crosses zero three times but is counted once
+        let input = input + "L250\n";
+        assert_eq!(part_two(&input), Some(9));
+        assert_eq!(count_crossing_rotations(&input), 7);
+    }
+
+    #[test]
+    fn test_count_landings_on() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        assert_eq!(count_landings_on(&input, &[0, 25, 50, 75], 100), Some(3));
+        assert_eq!(count_landings_on(&input, &[0, 5, 45], 100), Some(5));
+        assert_eq!(count_landings_on(&input, &[20, 28], 40), Some(4));
+        assert_eq!(count_landings_on(&input, &[0], 0), None);
+        assert_eq!(count_landings_on(&input, &[0], -100), None);
+    }
+
+    #[test]
+    fn test_net_rotation() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        // 340 clicks left and 122 clicks right
+        assert_eq!(net_rotation(&input), 218);
+        assert_eq!(winding_number(&input, 100), Some(2));
+        assert_eq!(winding_number(&input, 40), Some(5));
+        assert_eq!(winding_number(&input, 0), None);
+
+        assert_eq!(net_rotation("R250\nL20\n"), -230);
+        assert_eq!(winding_number("R250\nL20\n", 100), Some(-2));
+    }
+
+    #[test]
+    fn test_first_turn_threshold() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        // L68 passes zero, then R48 lands on it
+        assert_eq!(first_turn_threshold(&input, 1, 100), Some(1));
+        assert_eq!(first_turn_threshold(&input, 2, 100), Some(3));
+        assert_eq!(first_turn_threshold(&input, 6, 100), Some(10));
+        assert_eq!(first_turn_threshold(&input, 7, 100), None);
+        assert_eq!(first_turn_threshold(&input, 7, 40), Some(8));
+        assert_eq!(first_turn_threshold(&input, 1, 0), None);
+
+        // On a 40-position dial the start wraps to 10, so L68 passes zero only once
+        assert_eq!(first_turn_threshold("L68\n", 1, 40), Some(1));
+        assert_eq!(first_turn_threshold("L68\n", 2, 40), None);
+        assert_eq!(first_turn_threshold(&input, 0, 100), Some(0));
+    }
+
+    #[test]
+    fn test_cycle_length() {
+        // Each pass moves the dial 20 clicks, so it takes 5 passes to come back around
+        assert_eq!(cycle_length("L25\nR5\n", 10), Some(5));
+        assert_eq!(cycle_length("L25\nR5\n", 4), None);
+        assert_eq!(cycle_length("L30\nR30\n", 10), Some(1));
+    }
 }
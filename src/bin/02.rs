@@ -1,5 +1,6 @@
 advent_of_code::solution!(2);
 
+use advent_of_code::digits::to_digits;
 use std::ops::RangeInclusive;
 
 /// Parse a range string like "11-22" into a RangeInclusive
@@ -13,8 +14,8 @@ fn parse_range(s: &str) -> Option<RangeInclusive<u64>> {
 /// Check if a number is made of exactly two repetitions of a pattern
 #[inline]
 fn has_two_repetitions(n: u64) -> bool {
-    let s = n.to_string();
-    let len = s.len();
+    let digits = to_digits(n);
+    let len = digits.len();
 
     // Must be even length to split into two equal parts
     if !len.is_multiple_of(2) {
@@ -22,16 +23,16 @@ fn has_two_repetitions(n: u64) -> bool {
     }
 
     let mid = len / 2;
-    let (first, second) = s.split_at(mid);
+    let (first, second) = digits.split_at(mid);
 
-    !first.starts_with('0') && first == second
+    first[0] != 0 && first == second
 }
 
 /// Check if a number is made of any pattern repeated at least twice
 #[inline]
 fn has_repeating_pattern(n: u64) -> bool {
-    let s = n.to_string();
-    let len = s.len();
+    let digits = to_digits(n);
+    let len = digits.len();
 
     // Try all possible pattern lengths from 1 to len/2
     for pattern_len in 1..=(len / 2) {
@@ -39,19 +40,15 @@ fn has_repeating_pattern(n: u64) -> bool {
             continue;
         }
 
-        let pattern = &s[..pattern_len];
+        let pattern = &digits[..pattern_len];
 
         // Pattern can't have leading zeros
-        if pattern.starts_with('0') {
+        if pattern[0] == 0 {
             continue;
         }
 
         // Check if all chunks equal the pattern (avoids allocating via repeat())
-        let pattern_bytes = pattern.as_bytes();
-        if s.as_bytes()
-            .chunks(pattern_len)
-            .all(|chunk| chunk == pattern_bytes)
-        {
+        if digits.chunks(pattern_len).all(|chunk| chunk == pattern) {
             return true;
         }
     }
@@ -59,28 +56,64 @@ fn has_repeating_pattern(n: u64) -> bool {
     false
 }
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let sum: u64 = input
-        .trim()
-        .split(',')
-        .filter_map(parse_range)
+/// Sum every number matching `predicate` across a stream of ranges
+///
+/// Ranges are consumed one at a time, so the source never needs to be collected.
+fn sum_from_ranges<I: Iterator<Item = RangeInclusive<u64>>>(
+    ranges: I,
+    predicate: fn(u64) -> bool,
+) -> u64 {
+    ranges.flatten().filter(|&n| predicate(n)).sum()
+}
+
+/// Every number matching `predicate` across a stream of ranges, in range order,
+/// stopping after `cap` numbers if one is given
+fn numbers_from_ranges<I: Iterator<Item = RangeInclusive<u64>>>(
+    ranges: I,
+    predicate: fn(u64) -> bool,
+    cap: Option<usize>,
+) -> Vec<u64> {
+    ranges
         .flatten()
-        .filter(|&n| has_two_repetitions(n))
-        .sum();
+        .filter(|&n| predicate(n))
+        .take(cap.unwrap_or(usize::MAX))
+        .collect()
+}
 
-    Some(sum)
+/// The smallest number in `range` made of exactly two repetitions of a pattern
+#[allow(dead_code)]
+fn first_two_repetition(range: &RangeInclusive<u64>) -> Option<u64> {
+    range.clone().find(|&n| has_two_repetitions(n))
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let sum: u64 = input
-        .trim()
-        .split(',')
-        .filter_map(parse_range)
-        .flatten()
-        .filter(|&n| has_repeating_pattern(n))
-        .sum();
+/// The smallest number in `range` made of a pattern repeated at least twice
+#[allow(dead_code)]
+fn first_repeating_pattern(range: &RangeInclusive<u64>) -> Option<u64> {
+    range.clone().find(|&n| has_repeating_pattern(n))
+}
+
+/// The numbers `part_one` sums, stopping after `cap` numbers if one is given
+#[allow(dead_code)]
+fn two_repetition_numbers(input: &str, cap: Option<usize>) -> Vec<u64> {
+    let ranges = input.trim().split(',').filter_map(parse_range);
+    numbers_from_ranges(ranges, has_two_repetitions, cap)
+}
+
+/// The numbers `part_two` sums, stopping after `cap` numbers if one is given
+#[allow(dead_code)]
+fn repeating_pattern_numbers(input: &str, cap: Option<usize>) -> Vec<u64> {
+    let ranges = input.trim().split(',').filter_map(parse_range);
+    numbers_from_ranges(ranges, has_repeating_pattern, cap)
+}
+
+pub fn part_one(input: &str) -> Option<u64> {
+    let ranges = input.trim().split(',').filter_map(parse_range);
+    Some(sum_from_ranges(ranges, has_two_repetitions))
+}
 
-    Some(sum)
+pub fn part_two(input: &str) -> Option<u64> {
+    let ranges = input.trim().split(',').filter_map(parse_range);
+    Some(sum_from_ranges(ranges, has_repeating_pattern))
 }
 
 #[cfg(test)]
@@ -98,4 +131,35 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(4174379265));
     }
+
+    #[test]
+    fn test_sum_from_ranges() {
+        let ranges = [11..=22, 95..=115].into_iter();
+        assert_eq!(sum_from_ranges(ranges.clone(), has_two_repetitions), 132);
+        assert_eq!(sum_from_ranges(ranges, has_repeating_pattern), 243);
+    }
+
+    #[test]
+    fn test_first_match() {
+        assert_eq!(first_two_repetition(&(100..=1200)), Some(1010));
+        assert_eq!(first_repeating_pattern(&(100..=1200)), Some(111));
+        assert_eq!(first_two_repetition(&(95..=115)), Some(99));
+        assert_eq!(first_two_repetition(&(12..=21)), None);
+        assert_eq!(first_repeating_pattern(&(12..=21)), None);
+    }
+
+    #[test]
+    fn test_qualifying_numbers() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let two = two_repetition_numbers(&input, None);
+        let repeating = repeating_pattern_numbers(&input, None);
+        assert_eq!(Some(two.iter().sum()), part_one(&input));
+        assert_eq!(Some(repeating.iter().sum()), part_two(&input));
+
+        assert_eq!(two_repetition_numbers(&input, Some(3)), vec![11, 22, 99]);
+        assert_eq!(
+            repeating_pattern_numbers(&input, Some(4)),
+            vec![11, 22, 99, 111]
+        );
+    }
 }
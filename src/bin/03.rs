@@ -1,5 +1,32 @@
 advent_of_code::solution!(3);
 
+use advent_of_code::digits::from_ascii_digits;
+
+/// Parse a bank's decimal digits, skipping anything else
+fn parse_digits(bank: &str) -> Vec<u8> {
+    from_ascii_digits(bank.as_bytes())
+}
+
+/// Parse a bank's digits in the given radix (up to 36, so `A`-`F` are 10-15 in hex)
+/// directly from bytes for efficiency, skipping anything else
+fn parse_digits_radix(bank: &str, radix: u32) -> Vec<u8> {
+    if radix == 10 {
+        return parse_digits(bank);
+    }
+
+    bank.bytes()
+        .filter_map(|b| char::from(b).to_digit(radix))
+        .map(|d| d as u8)
+        .collect()
+}
+
+/// Fold digits into their decimal value
+fn to_joltage(digits: impl IntoIterator<Item = u8>) -> u64 {
+    digits
+        .into_iter()
+        .fold(0, |acc, digit| acc * 10 + digit as u64)
+}
+
 /// Find the maximum joltage by selecting exactly `count` batteries using a greedy algorithm.
 ///
 /// Strategy: For each position (left to right), choose the largest available digit
@@ -7,37 +34,141 @@ advent_of_code::solution!(3);
 /// When ties occur, we select the leftmost maximum to preserve flexibility.
 #[inline]
 fn max_joltage(bank: &str, count: usize) -> u64 {
-    // Parse digits directly from bytes for efficiency
-    let digits: Vec<u8> = bank
-        .bytes()
-        .filter(|&b| b.is_ascii_digit())
-        .map(|b| b - b'0')
-        .collect();
+    max_joltage_radix(bank, count, 10)
+}
+
+/// Which battery the greedy selection takes when a window's maximum digit appears more than once
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TiePolicy {
+    Leftmost,
+    #[allow(dead_code)]
+    Rightmost,
+}
+
+/// `max_joltage` for banks whose batteries are digits in the given radix, maximizing the
+/// resulting base-`radix` number.
+///
+/// Panics unless `radix` is between 2 and 36, the radixes `char::to_digit` understands.
+fn max_joltage_radix(bank: &str, count: usize, radix: u32) -> u64 {
+    assert!(
+        (2..=36).contains(&radix),
+        "radix must be between 2 and 36, got {radix}"
+    );
+    let digits = parse_digits_radix(bank, radix);
+
+    select_batteries(&digits, count, TiePolicy::Leftmost).map_or(0, |indices| {
+        indices
+            .into_iter()
+            .fold(0, |acc, i| acc * radix as u64 + digits[i] as u64)
+    })
+}
+
+/// `max_joltage` with a choice of which maximum digit to take in each window.
+///
+/// On ties only in the last window, or ties that no later window needs, both policies build
+/// the same number from different batteries. `Rightmost` skips past the earlier copies,
+/// though, so it can lose a digit a later window needed: `"991"` with two batteries gives
+/// 99 when leftmost but 91 when rightmost.
+#[allow(dead_code)]
+fn max_joltage_with_policy(bank: &str, count: usize, policy: TiePolicy) -> u64 {
+    let digits = parse_digits(bank);
+    select_batteries(&digits, count, policy).map_or(0, |indices| {
+        to_joltage(indices.into_iter().map(|i| digits[i]))
+    })
+}
+
+/// The batteries `max_joltage` selects, as their digits in bank order, or None if the bank
+/// has fewer than `count` batteries
+#[allow(dead_code)]
+fn max_joltage_string(bank: &str, count: usize) -> Option<String> {
+    let batteries: Vec<char> = bank.chars().filter(char::is_ascii_digit).collect();
+    let indices = select_batteries(&parse_digits(bank), count, TiePolicy::Leftmost)?;
+    Some(indices.into_iter().map(|i| batteries[i]).collect())
+}
 
+/// Indices of the `count` batteries the greedy selection picks, or None if the bank has
+/// fewer than `count` batteries
+fn select_batteries(digits: &[u8], count: usize, policy: TiePolicy) -> Option<Vec<usize>> {
     if digits.len() < count {
-        return 0;
+        return None;
     }
 
-    let mut result = 0u64;
+    let mut indices = Vec::with_capacity(count);
     let mut position = 0;
 
     for i in 0..count {
         // Calculate the search window: must leave enough digits for remaining positions
         let remaining_needed = count - i - 1;
-        let window_end = digits.len() - remaining_needed;
+        let window = &digits[position..digits.len() - remaining_needed];
 
-        // Find the first occurrence of the maximum digit in this window
-        let max_digit = *digits[position..window_end].iter().max().unwrap();
-        let offset = digits[position..window_end]
-            .iter()
-            .position(|&d| d == max_digit)
-            .unwrap();
+        // Find the first (or last) occurrence of the maximum digit in this window
+        let max_digit = *window.iter().max()?;
+        let offset = match policy {
+            TiePolicy::Leftmost => window.iter().position(|&d| d == max_digit),
+            TiePolicy::Rightmost => window.iter().rposition(|&d| d == max_digit),
+        }?;
 
-        result = result * 10 + max_digit as u64;
+        indices.push(position + offset);
         position += offset + 1;
     }
 
-    result
+    Some(indices)
+}
+
+/// Longest bank `max_joltage_exhaustive` will search
+const EXHAUSTIVE_MAX_BANK: usize = 16;
+
+/// Find the maximum joltage by trying every subsequence of exactly `count` batteries.
+///
+/// Exponential in the bank length, so only meant for cross-checking `max_joltage` on
+/// short banks. Returns None for banks longer than `EXHAUSTIVE_MAX_BANK`.
+#[allow(dead_code)]
+fn max_joltage_exhaustive(bank: &str, count: usize) -> Option<u64> {
+    fn search(digits: &[u8], count: usize, acc: u64) -> u64 {
+        if count == 0 {
+            return acc;
+        }
+
+        (0..=digits.len().saturating_sub(count))
+            .map(|i| search(&digits[i + 1..], count - 1, acc * 10 + digits[i] as u64))
+            .max()
+            .unwrap_or(0)
+    }
+
+    let digits = parse_digits(bank);
+
+    if digits.len() > EXHAUSTIVE_MAX_BANK {
+        return None;
+    }
+    if digits.len() < count {
+        return Some(0);
+    }
+
+    Some(search(&digits, count, 0))
+}
+
+/// Find the maximum joltage when batteries may be reused and order doesn't matter,
+/// which is just the largest digit repeated `count` times
+#[allow(dead_code)]
+fn max_joltage_with_repetition(bank: &str, count: usize) -> u64 {
+    parse_digits(bank).into_iter().max().map_or(0, |max_digit| {
+        to_joltage(std::iter::repeat_n(max_digit, count))
+    })
+}
+
+/// Find the maximum joltage from the `count` largest distinct digits in descending order,
+/// or 0 if the bank has fewer than `count` distinct digits
+#[allow(dead_code)]
+fn max_joltage_top_k_distinct(bank: &str, count: usize) -> u64 {
+    let mut digits = parse_digits(bank);
+    digits.sort_unstable_by(|a, b| b.cmp(a));
+    digits.dedup();
+
+    if digits.len() < count {
+        return 0;
+    }
+
+    to_joltage(digits.into_iter().take(count))
 }
 
 pub fn part_one(input: &str) -> Option<u64> {
@@ -63,4 +194,101 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(3121910778619));
     }
+
+    #[test]
+    fn test_greedy_matches_exhaustive() {
+        let banks = [
+            "987654321",
+            "811111119",
+            "234234278",
+            "1212121",
+            "9",
+            "5115",
+        ];
+        for bank in banks {
+            for count in 1..=bank.len().min(4) {
+                assert_eq!(
+                    Some(max_joltage(bank, count)),
+                    max_joltage_exhaustive(bank, count),
+                    "bank {bank}, count {count}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_exhaustive_bank_limit() {
+        let bank = "1234567890123456";
+        assert_eq!(max_joltage_exhaustive(bank, 2), Some(96));
+        assert_eq!(max_joltage_exhaustive(&format!("{bank}7"), 2), None);
+    }
+
+    #[test]
+    fn test_hex_bank() {
+        let bank = "1F3A2";
+        assert_eq!(max_joltage_radix(bank, 2, 16), 0xFA);
+        assert_eq!(max_joltage_radix(bank, 3, 16), 0xFA2);
+        assert_eq!(max_joltage_radix("1f3a2", 2, 16), 0xFA);
+
+        // In base 10 the letters aren't batteries at all
+        assert_eq!(max_joltage(bank, 2), 32);
+    }
+
+    #[test]
+    #[should_panic(expected = "radix must be between 2 and 36")]
+    fn test_radix_too_large() {
+        max_joltage_radix("123", 2, 37);
+    }
+
+    #[test]
+    #[should_panic(expected = "radix must be between 2 and 36")]
+    fn test_radix_too_small() {
+        max_joltage_radix("101", 2, 1);
+    }
+
+    #[test]
+    fn test_max_joltage_string() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        for bank in input.lines() {
+            for count in [2, 12] {
+                let selected = max_joltage_string(bank, count).unwrap();
+                assert_eq!(selected.len(), count);
+                assert_eq!(selected.parse::<u64>().unwrap(), max_joltage(bank, count));
+            }
+        }
+
+        assert_eq!(max_joltage_string("8 1 9", 2).as_deref(), Some("89"));
+        assert_eq!(max_joltage_string("12", 3), None);
+    }
+
+    #[test]
+    fn test_tie_policy() {
+        let digits = parse_digits("911");
+        assert_eq!(
+            select_batteries(&digits, 2, TiePolicy::Leftmost),
+            Some(vec![0, 1])
+        );
+        assert_eq!(
+            select_batteries(&digits, 2, TiePolicy::Rightmost),
+            Some(vec![0, 2])
+        );
+        assert_eq!(max_joltage_with_policy("911", 2, TiePolicy::Leftmost), 91);
+        assert_eq!(max_joltage_with_policy("911", 2, TiePolicy::Rightmost), 91);
+
+        // Skipping the first 9 leaves only the 1 for the second battery
+        assert_eq!(max_joltage_with_policy("991", 2, TiePolicy::Leftmost), 99);
+        assert_eq!(max_joltage_with_policy("991", 2, TiePolicy::Rightmost), 91);
+        assert_eq!(select_batteries(&digits, 4, TiePolicy::Leftmost), None);
+    }
+
+    #[test]
+    fn test_selection_modes() {
+        let bank = "818181911112111";
+        assert_eq!(max_joltage(bank, 3), 921);
+        assert_eq!(max_joltage_with_repetition(bank, 3), 999);
+        assert_eq!(max_joltage_top_k_distinct(bank, 3), 982);
+
+        assert_eq!(max_joltage_top_k_distinct("5115", 3), 0);
+        assert_eq!(max_joltage_with_repetition("", 2), 0);
+    }
 }
@@ -1,5 +1,7 @@
 advent_of_code::solution!(4);
 
+use advent_of_code::grid::peel;
+
 /// The 8 directions for checking neighbors (row_delta, col_delta)
 const DIRECTIONS: [(i32, i32); 8] = [
     (-1, -1),
@@ -12,33 +14,46 @@ const DIRECTIONS: [(i32, i32); 8] = [
     (1, 1),
 ];
 
-/// Count how many paper roll neighbors a position has
+/// The cell value marking a paper roll
+const PAPER_ROLL: u8 = b'@';
+
+/// Yield the in-bounds coordinates of the 8 neighbors of a position
 #[inline]
-fn count_neighbors(grid: &[Vec<u8>], row: usize, col: usize) -> usize {
+fn neighbors8(grid: &[Vec<u8>], row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> {
     let height = grid.len() as i32;
     let width = grid[0].len() as i32;
 
-    DIRECTIONS
-        .iter()
-        .filter(|(dr, dc)| {
-            let nr = row as i32 + dr;
-            let nc = col as i32 + dc;
-            nr >= 0
-                && nr < height
-                && nc >= 0
-                && nc < width
-                && grid[nr as usize][nc as usize] == b'@'
-        })
+    DIRECTIONS.iter().filter_map(move |(dr, dc)| {
+        let nr = row as i32 + dr;
+        let nc = col as i32 + dc;
+        if nr >= 0 && nr < height && nc >= 0 && nc < width {
+            Some((nr as usize, nc as usize))
+        } else {
+            None
+        }
+    })
+}
+
+/// Count how many neighbors of a position hold the target value
+#[inline]
+fn count_neighbors(grid: &[Vec<u8>], row: usize, col: usize, target: u8) -> usize {
+    neighbors8(grid, row, col)
+        .filter(|&(nr, nc)| grid[nr][nc] == target)
         .count()
 }
 
-/// Find all accessible paper rolls (those with fewer than 4 neighbors)
-fn find_accessible(grid: &[Vec<u8>]) -> Vec<(usize, usize)> {
+/// Find all accessible paper rolls (those with fewer than 4 roll neighbors)
+fn find_accessible_rolls(grid: &[Vec<u8>]) -> Vec<(usize, usize)> {
+    find_accessible(grid, PAPER_ROLL)
+}
+
+/// Find all target cells with fewer than 4 neighbors holding the same target value
+fn find_accessible(grid: &[Vec<u8>], target: u8) -> Vec<(usize, usize)> {
     grid.iter()
         .enumerate()
         .flat_map(|(row, line)| {
             line.iter().enumerate().filter_map(move |(col, &cell)| {
-                if cell == b'@' && count_neighbors(grid, row, col) < 4 {
+                if cell == target && count_neighbors(grid, row, col, target) < 4 {
                     Some((row, col))
                 } else {
                     None
@@ -48,30 +63,102 @@ fn find_accessible(grid: &[Vec<u8>]) -> Vec<(usize, usize)> {
         .collect()
 }
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let grid: Vec<Vec<u8>> = input.lines().map(|line| line.bytes().collect()).collect();
-    Some(find_accessible(&grid).len() as u64)
-}
-
-pub fn part_two(input: &str) -> Option<u64> {
+/// Peel accessible rolls round by round, like `part_two`, but keep a grid of roll
+/// neighbor counts that is only updated around the cells removed in each round
+#[allow(dead_code)]
+fn peel_incremental(input: &str) -> u64 {
     let mut grid: Vec<Vec<u8>> = input.lines().map(|line| line.bytes().collect()).collect();
+    if grid.is_empty() {
+        return 0;
+    }
+
+    let mut counts: Vec<Vec<usize>> = (0..grid.len())
+        .map(|row| {
+            (0..grid[row].len())
+                .map(|col| count_neighbors(&grid, row, col, PAPER_ROLL))
+                .collect()
+        })
+        .collect();
+
+    let mut accessible = find_accessible_rolls(&grid);
     let mut total = 0;
 
-    loop {
-        let accessible = find_accessible(&grid);
-        if accessible.is_empty() {
-            break;
+    while !accessible.is_empty() {
+        for &(row, col) in &accessible {
+            grid[row][col] = b'.';
         }
+        total += accessible.len() as u64;
 
-        // Remove all accessible rolls
-        for (row, col) in &accessible {
-            grid[*row][*col] = b'.';
+        // Only neighbors of removed rolls can become accessible in the next round
+        let mut next = Vec::new();
+        for &(row, col) in &accessible {
+            for (nr, nc) in neighbors8(&grid, row, col) {
+                counts[nr][nc] -= 1;
+                if grid[nr][nc] == PAPER_ROLL && counts[nr][nc] == 3 {
+                    next.push((nr, nc));
+                }
+            }
         }
 
-        total += accessible.len() as u64;
+        accessible = next;
     }
 
-    Some(total)
+    total
+}
+
+/// The grid with every currently accessible roll drawn as `o`, one line per row
+#[allow(dead_code)]
+fn render_accessible(input: &str) -> String {
+    let mut grid: Vec<Vec<u8>> = input.lines().map(|line| line.bytes().collect()).collect();
+    for (row, col) in find_accessible_rolls(&grid) {
+        grid[row][col] = b'o';
+    }
+
+    grid.into_iter()
+        .map(|row| String::from_utf8(row).unwrap() + "\n")
+        .collect()
+}
+
+pub fn part_one(input: &str) -> Option<u64> {
+    let grid: Vec<Vec<u8>> = input.lines().map(|line| line.bytes().collect()).collect();
+    Some(find_accessible_rolls(&grid).len() as u64)
+}
+
+/// Remove accessible rolls round by round, until nothing is accessible, returning how
+/// many each round removed
+fn peel_rolls(grid: &mut [Vec<u8>]) -> Vec<u64> {
+    peel(grid, |grid, row, col| {
+        grid[row][col] == PAPER_ROLL && count_neighbors(grid, row, col, PAPER_ROLL) < 4
+    })
+    .into_iter()
+    .map(|count| count as u64)
+    .collect()
+}
+
+/// Number of rolls removed in each round of peeling, until nothing is accessible
+fn removal_rounds(input: &str) -> Vec<u64> {
+    let mut grid: Vec<Vec<u8>> = input.lines().map(|line| line.bytes().collect()).collect();
+    peel_rolls(&mut grid)
+}
+
+/// Total rolls removed by peeling, and the grid left afterwards, where every remaining
+/// roll has at least 4 roll neighbors and so can never be removed
+fn peel_to_fixpoint(input: &str) -> (u64, Vec<Vec<u8>>) {
+    let mut grid: Vec<Vec<u8>> = input.lines().map(|line| line.bytes().collect()).collect();
+    let removed = peel_rolls(&mut grid).iter().sum();
+    (removed, grid)
+}
+
+/// Number of rolls removed on round `n` (1-based), or 0 if peeling stops before then
+#[allow(dead_code)]
+fn rolls_in_round(input: &str, n: usize) -> u64 {
+    n.checked_sub(1)
+        .and_then(|idx| removal_rounds(input).get(idx).copied())
+        .unwrap_or(0)
+}
+
+pub fn part_two(input: &str) -> Option<u64> {
+    Some(peel_to_fixpoint(input).0)
 }
 
 #[cfg(test)]
@@ -89,4 +176,121 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(43));
     }
+
+    #[test]
+    fn test_neighbors8() {
+        let grid: Vec<Vec<u8>> = vec![b"...".to_vec(); 3];
+        let sorted = |row, col| {
+            let mut cells: Vec<_> = neighbors8(&grid, row, col).collect();
+            cells.sort_unstable();
+            cells
+        };
+
+        assert_eq!(sorted(0, 0), vec![(0, 1), (1, 0), (1, 1)]);
+        assert_eq!(sorted(0, 1), vec![(0, 0), (0, 2), (1, 0), (1, 1), (1, 2)]);
+        assert_eq!(
+            sorted(1, 1),
+            vec![
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (1, 2),
+                (2, 0),
+                (2, 1),
+                (2, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rolls_in_round() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let counts: Vec<u64> = (1..=10).map(|n| rolls_in_round(&input, n)).collect();
+        assert_eq!(counts, vec![13, 12, 7, 5, 2, 1, 1, 1, 1, 0]);
+        assert_eq!(rolls_in_round(&input, 0), 0);
+    }
+
+    #[test]
+    fn test_other_target() {
+        let grid: Vec<Vec<u8>> = advent_of_code::template::read_file("examples", DAY)
+            .lines()
+            .map(|line| line.bytes().collect())
+            .collect();
+
+        assert_eq!(count_neighbors(&grid, 0, 0, PAPER_ROLL), 2);
+        assert_eq!(count_neighbors(&grid, 0, 0, b'.'), 1);
+        // All but one empty cell has fewer than 4 empty neighbors
+        assert_eq!(find_accessible(&grid, b'.').len(), 28);
+    }
+
+    #[test]
+    fn test_peel_incremental() {
+        let example = advent_of_code::template::read_file("examples", DAY);
+        assert_eq!(Some(peel_incremental(&example)), part_two(&example));
+
+        // A dense pseudo-random 60x60 grid
+        let mut state = 12345u32;
+        let synthetic: String = (0..60)
+            .map(|_| {
+                let mut line: String = (0..60)
+                    .map(|_| {
+                        state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                        if (state >> 16).is_multiple_of(5) {
+                            '.'
+                        } else {
+                            '@'
+                        }
+                    })
+                    .collect();
+                line.push('\n');
+                line
+            })
+            .collect();
+        assert_eq!(Some(peel_incremental(&synthetic)), part_two(&synthetic));
+    }
+
+    #[test]
+    fn test_render_accessible() {
+        let rendered = render_accessible(&advent_of_code::template::read_file("examples", DAY));
+        let expected = "\
+..oo.oo@o.
+o@@.@.@.@@
+@@@@@.o.@@
+@.@@@@..@.
+o@.@@@@.@o
+.@@@@@@@.@
+.@.@.@.@@@
+o.@@@.@@@@
+.@@@@@@@@.
+o.o.@@@.o.
+";
+        assert_eq!(rendered, expected);
+        assert_eq!(rendered.matches('o').count(), 13);
+    }
+
+    #[test]
+    fn test_peel_to_fixpoint() {
+        // Every roll of the octagon has at least 4 roll neighbors, even on its rim,
+        // so only the lone roll on the right is ever removed
+        let input = ".@@@...\n@@@@@.@\n@@@@@..\n.@@@...\n";
+        let (removed, grid) = peel_to_fixpoint(input);
+        assert_eq!(removed, 1);
+        let expected: Vec<Vec<u8>> = [".@@@...", "@@@@@..", "@@@@@..", ".@@@..."]
+            .iter()
+            .map(|row| row.bytes().collect())
+            .collect();
+        assert_eq!(grid, expected);
+
+        let example = advent_of_code::template::read_file("examples", DAY);
+        let (removed, grid) = peel_to_fixpoint(&example);
+        assert_eq!(removed, 43);
+        assert_eq!(
+            grid.iter()
+                .flatten()
+                .filter(|&&cell| cell == PAPER_ROLL)
+                .count(),
+            28
+        );
+    }
 }
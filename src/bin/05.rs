@@ -1,5 +1,6 @@
 advent_of_code::solution!(5);
 
+use advent_of_code::ranges::IntervalSet;
 use std::ops::RangeInclusive;
 
 #[inline]
@@ -35,6 +36,53 @@ fn merge_ranges(mut ranges: Vec<RangeInclusive<u64>>) -> Vec<RangeInclusive<u64>
     merged
 }
 
+/// Insert one range into a sorted list of disjoint, non-adjacent ranges (as produced by
+/// `merge_ranges`), coalescing it with any neighbours it overlaps or touches
+#[allow(dead_code)]
+fn insert_merged(merged: &mut Vec<RangeInclusive<u64>>, r: RangeInclusive<u64>) {
+    if r.is_empty() {
+        return;
+    }
+
+    // Ranges in first..last overlap or are adjacent to r
+    let first = merged.partition_point(|m| m.end().saturating_add(1) < *r.start());
+    let last = merged.partition_point(|m| *m.start() <= r.end().saturating_add(1));
+
+    let (mut start, mut end) = r.into_inner();
+    if first < last {
+        start = start.min(*merged[first].start());
+        end = end.max(*merged[last - 1].end());
+    }
+    merged.splice(first..last, [start..=end]);
+}
+
+/// Largest number of integers `covered_integers` will list
+const COVERED_INTEGERS_CAP: u64 = 1_000_000;
+
+/// All integers covered by the fresh ranges, in ascending order.
+/// Returns None if there are more than `COVERED_INTEGERS_CAP` of them, including when
+/// the count itself overflows a u64.
+#[allow(dead_code)]
+fn covered_integers(input: &str) -> Option<Vec<u64>> {
+    let merged = merge_ranges(parse_ranges(input.split("\n\n").next()?));
+    merged
+        .iter()
+        .try_fold(0u64, |total, r| {
+            (r.end() - r.start()).checked_add(1)?.checked_add(total)
+        })
+        .filter(|&total| total <= COVERED_INTEGERS_CAP)?;
+
+    Some(merged.into_iter().flatten().collect())
+}
+
+/// Number of `ids` contained in at least `k` of the original (unmerged) ranges
+#[allow(dead_code)]
+fn count_covered_at_least(ranges: &[RangeInclusive<u64>], ids: &[u64], k: usize) -> u64 {
+    ids.iter()
+        .filter(|id| ranges.iter().filter(|range| range.contains(id)).count() >= k)
+        .count() as u64
+}
+
 pub fn part_one(input: &str) -> Option<u64> {
     let mut sections = input.split("\n\n");
     let ranges = parse_ranges(sections.next()?);
@@ -44,14 +92,9 @@ pub fn part_one(input: &str) -> Option<u64> {
         .filter_map(|line| line.parse().ok())
         .collect();
 
-    let merged = merge_ranges(ranges);
-
-    let count = ids
-        .iter()
-        .filter(|id| merged.iter().any(|range| range.contains(id)))
-        .count();
+    let fresh = IntervalSet::from_merged(merge_ranges(ranges));
 
-    Some(count as u64)
+    Some(fresh.covered_count(&ids) as u64)
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
@@ -76,4 +119,61 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(14));
     }
+
+    #[test]
+    fn test_count_covered_at_least() {
+        let ranges = [1..=10, 5..=15, 8..=20, 30..=40];
+        let ids = [3, 6, 9, 12, 18, 35, 50];
+        assert_eq!(count_covered_at_least(&ranges, &ids, 1), 6);
+        assert_eq!(count_covered_at_least(&ranges, &ids, 2), 3);
+        assert_eq!(count_covered_at_least(&ranges, &ids, 3), 1);
+        assert_eq!(count_covered_at_least(&ranges, &ids, 0), 7);
+    }
+
+    #[test]
+    fn test_insert_merged() {
+        let mut merged = vec![1..=3, 10..=12];
+        insert_merged(&mut merged, 4..=5);
+        assert_eq!(merged, vec![1..=5, 10..=12]);
+        insert_merged(&mut merged, 7..=7);
+        assert_eq!(merged, vec![1..=5, 7..=7, 10..=12]);
+        insert_merged(&mut merged, 6..=9);
+        assert_eq!(merged, vec![1..=12]);
+        insert_merged(&mut merged, 20..=u64::MAX);
+        assert_eq!(merged, vec![1..=12, 20..=u64::MAX]);
+    }
+
+    #[test]
+    fn test_insert_merged_matches_batch() {
+        // Every sequence of three small ranges, covering overlapping, adjacent,
+        // nested and disjoint inserts in every order
+        let small: Vec<RangeInclusive<u64>> = (0..5)
+            .flat_map(|start| (0..3).map(move |len| start..=start + len))
+            .collect();
+
+        for a in &small {
+            for b in &small {
+                for c in &small {
+                    let ranges = vec![a.clone(), b.clone(), c.clone()];
+                    let mut incremental = Vec::new();
+                    for range in ranges.iter().cloned() {
+                        insert_merged(&mut incremental, range);
+                    }
+                    assert_eq!(incremental, merge_ranges(ranges.clone()), "{ranges:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_covered_integers() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let covered = covered_integers(&input).unwrap();
+        assert_eq!(covered.len() as u64, part_two(&input).unwrap());
+        assert_eq!(&covered[..4], &[3, 4, 5, 10]);
+
+        assert_eq!(covered_integers("1-10000000\n\n1\n"), None);
+        assert_eq!(covered_integers("0-18446744073709551615\n\n1\n"), None);
+        assert_eq!(part_one("0-18446744073709551615\n\n1\n"), Some(1));
+    }
 }
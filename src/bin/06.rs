@@ -6,6 +6,16 @@ struct Problem {
     operator: char,
 }
 
+/// How the rows of a problem block are read when evaluating horizontally
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RowLayout {
+    /// Each row holds one number
+    SingleNumber,
+    /// Each row holds right-aligned fixed-width fields separated by whitespace
+    #[allow(dead_code)]
+    FixedWidthFields,
+}
+
 struct Worksheet {
     lines: Vec<Vec<u8>>,
     operator_line: Vec<u8>,
@@ -19,12 +29,15 @@ impl Worksheet {
             return None;
         }
 
+        let operator_idx = Self::detect_operator_line(&lines)?;
         let max_len = lines.iter().map(|l| l.len()).max()?;
-        let operator_line = Self::pad_bytes(lines.last()?.as_bytes(), max_len);
+        let operator_line = Self::pad_bytes(lines[operator_idx].as_bytes(), max_len);
 
-        let padded_lines = lines[..lines.len() - 1]
+        let padded_lines = lines
             .iter()
-            .map(|line| Self::pad_bytes(line.as_bytes(), max_len))
+            .enumerate()
+            .filter(|&(idx, line)| idx != operator_idx && !line.trim().is_empty())
+            .map(|(_, line)| Self::pad_bytes(line.as_bytes(), max_len))
             .collect();
 
         Some(Worksheet {
@@ -34,6 +47,16 @@ impl Worksheet {
         })
     }
 
+    /// Find the operator line: the last line made up only of operators and whitespace
+    fn detect_operator_line(lines: &[&str]) -> Option<usize> {
+        lines.iter().rposition(|line| {
+            line.bytes().any(is_operator)
+                && line
+                    .bytes()
+                    .all(|ch| is_operator(ch) || ch.is_ascii_whitespace())
+        })
+    }
+
     fn pad_bytes(bytes: &[u8], len: usize) -> Vec<u8> {
         let mut padded = bytes.to_vec();
         padded.resize(len, b' ');
@@ -41,14 +64,13 @@ impl Worksheet {
     }
 
     fn has_content_at(&self, col: usize) -> bool {
-        self.lines.iter().any(|line| line[col] != b' ')
-            || (self.operator_line[col] == b'*' || self.operator_line[col] == b'+')
+        self.lines.iter().any(|line| line[col] != b' ') || is_operator(self.operator_line[col])
     }
 
     fn find_operator(&self, start: usize, end: usize) -> Option<char> {
         self.operator_line[start..end]
             .iter()
-            .find(|&&ch| ch == b'*' || ch == b'+')
+            .find(|&&ch| is_operator(ch))
             .map(|&ch| ch as char)
     }
 
@@ -79,20 +101,48 @@ impl Worksheet {
         problems
     }
 
-    fn evaluate_horizontal(&self, problem: &Problem) -> u64 {
-        let numbers: Vec<u64> = self
-            .lines
+    fn evaluate_horizontal(&self, problem: &Problem) -> Option<u64> {
+        self.evaluate_horizontal_with(problem, RowLayout::SingleNumber)
+    }
+
+    fn evaluate_horizontal_with(&self, problem: &Problem, layout: RowLayout) -> Option<u64> {
+        apply_operator(&self.horizontal_operands(problem, layout), problem.operator)
+    }
+
+    /// The numbers of a problem read row by row, top to bottom
+    fn horizontal_operands(&self, problem: &Problem, layout: RowLayout) -> Vec<u64> {
+        self.lines
             .iter()
-            .filter_map(|line| {
-                let slice = &line[problem.start_col..problem.end_col];
-                std::str::from_utf8(slice).ok()?.trim().parse::<u64>().ok()
+            .filter_map(|line| std::str::from_utf8(&line[problem.start_col..problem.end_col]).ok())
+            .flat_map(|block| -> Vec<u64> {
+                match layout {
+                    RowLayout::SingleNumber => block.trim().parse().ok().into_iter().collect(),
+                    RowLayout::FixedWidthFields => block
+                        .split_whitespace()
+                        .filter_map(|field| field.parse().ok())
+                        .collect(),
+                }
             })
-            .collect();
+            .collect()
+    }
 
-        apply_operator(&numbers, problem.operator)
+    /// Check that every non-blank row of a problem's block is a number, reporting the
+    /// first row that isn't (rows counted from 0, skipping the operator line)
+    fn validate_problem(&self, problem: &Problem) -> Result<(), String> {
+        for (row, line) in self.lines.iter().enumerate() {
+            let block = String::from_utf8_lossy(&line[problem.start_col..problem.end_col]);
+            let block = block.trim();
+            if !block.is_empty() && block.parse::<u64>().is_err() {
+                return Err(format!(
+                    "problem at columns {}..{}: row {row} is not a number: {block:?}",
+                    problem.start_col, problem.end_col
+                ));
+            }
+        }
+        Ok(())
     }
 
-    fn evaluate_vertical(&self, problem: &Problem) -> u64 {
+    fn evaluate_vertical(&self, problem: &Problem) -> Option<u64> {
         let numbers: Vec<u64> = (problem.start_col..problem.end_col)
             .rev()
             .filter_map(|col_idx| {
@@ -117,27 +167,108 @@ impl Worksheet {
     }
 }
 
-fn apply_operator(numbers: &[u64], operator: char) -> u64 {
+#[inline]
+fn is_operator(ch: u8) -> bool {
+    ch == b'*' || ch == b'+'
+}
+
+/// Combine the numbers with the operator, or None if the result overflows a u64
+fn apply_operator(numbers: &[u64], operator: char) -> Option<u64> {
     if numbers.is_empty() {
-        return 0;
+        return Some(0);
     }
 
     match operator {
-        '*' => numbers.iter().product(),
-        '+' => numbers.iter().sum(),
-        _ => 0,
+        '*' => numbers.iter().try_fold(1u64, |acc, &n| acc.checked_mul(n)),
+        '+' => numbers.iter().try_fold(0u64, |acc, &n| acc.checked_add(n)),
+        _ => Some(0),
+    }
+}
+
+/// Sum the problem results, or None if any problem (or the total) overflows
+fn checked_total(mut results: impl Iterator<Item = Option<u64>>) -> Option<u64> {
+    results.try_fold(0u64, |acc, result| acc.checked_add(result?))
+}
+
+/// Render each problem as its horizontal operands joined by the operator, followed by
+/// the result, e.g. `"12 + 34 = 46"`, or None if any result overflows
+#[allow(dead_code)]
+fn explain_problems(input: &str) -> Option<Vec<String>> {
+    let worksheet = Worksheet::parse(input)?;
+    let problems = worksheet.find_problems();
+
+    problems
+        .iter()
+        .map(|problem| {
+            let operands: Vec<String> = worksheet
+                .horizontal_operands(problem, RowLayout::SingleNumber)
+                .iter()
+                .map(u64::to_string)
+                .collect();
+            let separator = format!(" {} ", problem.operator);
+            Some(format!(
+                "{} = {}",
+                operands.join(&separator),
+                worksheet.evaluate_horizontal(problem)?
+            ))
+        })
+        .collect()
+}
+
+/// Number of `+` and `*` problems on the worksheet, in that order
+#[allow(dead_code)]
+fn operator_counts(input: &str) -> Option<(usize, usize)> {
+    let worksheet = Worksheet::parse(input)?;
+    let problems = worksheet.find_problems();
+    let count = |operator| problems.iter().filter(|p| p.operator == operator).count();
+
+    Some((count('+'), count('*')))
+}
+
+/// Index, horizontal result and vertical result of every problem whose two readings
+/// disagree, or None if any result overflows
+#[allow(dead_code)]
+fn evaluation_diffs(input: &str) -> Option<Vec<(usize, u64, u64)>> {
+    let worksheet = Worksheet::parse(input)?;
+    let mut diffs = Vec::new();
+
+    for (idx, problem) in worksheet.find_problems().iter().enumerate() {
+        let horizontal = worksheet.evaluate_horizontal(problem)?;
+        let vertical = worksheet.evaluate_vertical(problem)?;
+        if horizontal != vertical {
+            diffs.push((idx, horizontal, vertical));
+        }
+    }
+
+    Some(diffs)
+}
+
+/// `part_one`, but failing on the first malformed row or overflow instead of skipping it
+#[allow(dead_code)]
+fn part_one_strict(input: &str) -> Result<u64, String> {
+    let worksheet = Worksheet::parse(input).ok_or("worksheet has no operator line")?;
+    let problems = worksheet.find_problems();
+
+    for problem in &problems {
+        worksheet.validate_problem(problem)?;
     }
+
+    checked_total(
+        problems
+            .iter()
+            .map(|problem| worksheet.evaluate_horizontal(problem)),
+    )
+    .ok_or_else(|| "result overflows a u64".to_string())
 }
 
 pub fn part_one(input: &str) -> Option<u64> {
     let worksheet = Worksheet::parse(input)?;
     let problems = worksheet.find_problems();
 
-    Some(
+    checked_total(
         problems
             .iter()
-            .map(|problem| worksheet.evaluate_horizontal(problem))
-            .sum(),
+            .map(|problem| worksheet.evaluate_horizontal(problem)),
     )
 }
 
@@ -145,11 +276,10 @@ pub fn part_two(input: &str) -> Option<u64> {
     let worksheet = Worksheet::parse(input)?;
     let problems = worksheet.find_problems();
 
-    Some(
+    checked_total(
         problems
             .iter()
-            .map(|problem| worksheet.evaluate_vertical(problem))
-            .sum(),
+            .map(|problem| worksheet.evaluate_vertical(problem)),
     )
 }
 
@@ -168,4 +298,99 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(3263827));
     }
+
+    #[test]
+    fn test_detect_operator_line() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let lines: Vec<&str> = input.lines().collect();
+        assert_eq!(Worksheet::detect_operator_line(&lines), Some(3));
+        assert_eq!(Worksheet::detect_operator_line(&["12", "34"]), None);
+    }
+
+    #[test]
+    fn test_trailing_blank_line() {
+        let input = advent_of_code::template::read_file("examples", DAY) + "\n   \n";
+        assert_eq!(part_one(&input), Some(4277556));
+        assert_eq!(part_two(&input), Some(3263827));
+    }
+
+    #[test]
+    fn test_explain_problems() {
+        let explained = explain_problems(&advent_of_code::template::read_file("examples", DAY));
+        assert_eq!(
+            explained.unwrap(),
+            vec![
+                "123 * 45 * 6 = 33210",
+                "328 + 64 + 98 = 490",
+                "51 * 387 * 215 = 4243455",
+                "64 + 23 + 314 = 401",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_operator_counts() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        assert_eq!(operator_counts(&input), Some((2, 2)));
+        assert_eq!(operator_counts("1 2 3\n4 5 6\n+ + *"), Some((2, 1)));
+        assert_eq!(operator_counts("12"), None);
+    }
+
+    #[test]
+    fn test_validate_problem() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        assert_eq!(part_one_strict(&input), Ok(4277556));
+
+        // The stray 'x' makes the first problem silently drop that row
+        let corrupted = input.replacen("45", "4x", 1);
+        assert_eq!(part_one(&corrupted), Some(4277556 - 33210 + 123 * 6));
+        assert_eq!(
+            part_one_strict(&corrupted),
+            Err("problem at columns 0..3: row 1 is not a number: \"4x\"".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fixed_width_fields() {
+        let worksheet = Worksheet::parse(" 12  34\n123456 7\n+      ").unwrap();
+        let problems = worksheet.find_problems();
+        assert_eq!(problems.len(), 1);
+
+        // By default the multi-number row does not parse and is skipped
+        assert_eq!(worksheet.evaluate_horizontal(&problems[0]), Some(0));
+        assert_eq!(
+            worksheet.evaluate_horizontal_with(&problems[0], RowLayout::FixedWidthFields),
+            Some(12 + 34 + 123456 + 7)
+        );
+    }
+
+    #[test]
+    fn test_product_overflow() {
+        // 10^11 * 10^11 * 10^11 overflows u64
+        let input = "100000000000\n100000000000\n100000000000\n*           ";
+        assert_eq!(part_one(input), None);
+        assert_eq!(explain_problems(input), None);
+        assert_eq!(apply_operator(&[u64::MAX, 1], '*'), Some(u64::MAX));
+        assert_eq!(apply_operator(&[u64::MAX, 1], '+'), None);
+    }
+
+    #[test]
+    fn test_evaluation_diffs() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        assert_eq!(
+            evaluation_diffs(&input),
+            Some(vec![
+                (0, 33210, 8544),
+                (1, 490, 625),
+                (2, 4243455, 3253600),
+                (3, 401, 1058),
+            ])
+        );
+
+        // A symmetric block reads the same both ways
+        assert_eq!(
+            evaluation_diffs("11 12\n11 34\n+  + "),
+            Some(vec![(1, 46, 37)])
+        );
+    }
 }
@@ -1,5 +1,6 @@
 advent_of_code::solution!(7);
 
+use advent_of_code::validate::Validate;
 use std::collections::{HashMap, HashSet, VecDeque};
 
 /// Represents a tachyon manifold with splitters
@@ -7,7 +8,11 @@ struct Manifold {
     grid: Vec<Vec<u8>>,
     rows: usize,
     cols: usize,
-    start_col: usize,
+    start: (usize, usize),
+    /// When set, beams split past the left or right edge re-enter on the opposite side
+    wrap_horizontally: bool,
+    /// How many columns to each side of a splitter receive a beam
+    spread: usize,
 }
 
 #[derive(Debug)]
@@ -17,7 +22,7 @@ impl std::fmt::Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Failed to parse manifold: empty grid or no start position"
+            "Failed to parse manifold: empty grid, or not exactly one start position"
         )
     }
 }
@@ -28,7 +33,24 @@ impl TryFrom<&str> for Manifold {
     type Error = ParseError;
 
     fn try_from(input: &str) -> Result<Self, Self::Error> {
-        let grid: Vec<Vec<u8>> = input.lines().map(|line| line.as_bytes().to_vec()).collect();
+        Self::try_from(input.as_bytes())
+    }
+}
+
+impl TryFrom<&[u8]> for Manifold {
+    type Error = ParseError;
+
+    /// Parse the grid straight from bytes, splitting lines like `str::lines`
+    fn try_from(input: &[u8]) -> Result<Self, Self::Error> {
+        let mut grid: Vec<Vec<u8>> = input
+            .split(|&ch| ch == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line).to_vec())
+            .collect();
+
+        // A trailing newline (or empty input) leaves an empty final segment
+        if grid.last().is_some_and(Vec::is_empty) {
+            grid.pop();
+        }
 
         if grid.is_empty() {
             return Err(ParseError);
@@ -37,21 +59,61 @@ impl TryFrom<&str> for Manifold {
         let rows = grid.len();
         let cols = grid[0].len();
 
-        // Find the starting position 'S' using iterator methods
-        let start_col = grid[0]
-            .iter()
-            .position(|&ch| ch == b'S')
-            .ok_or(ParseError)?;
+        // Find the single starting position 'S' anywhere in the grid
+        let mut starts = grid.iter().enumerate().flat_map(|(row, line)| {
+            line.iter()
+                .enumerate()
+                .filter(|&(_, &ch)| ch == b'S')
+                .map(move |(col, _)| (row, col))
+        });
+        let start = starts.next().ok_or(ParseError)?;
+        if starts.next().is_some() {
+            return Err(ParseError);
+        }
 
         Ok(Manifold {
             grid,
             rows,
             cols,
-            start_col,
+            start,
+            wrap_horizontally: false,
+            spread: 1,
         })
     }
 }
 
+impl Validate for Manifold {
+    type Error = ParseError;
+
+    /// A manifold only needs exactly one start position
+    fn validate(input: &str) -> Result<(), Self::Error> {
+        match input.bytes().filter(|&ch| ch == b'S').count() {
+            1 => Ok(()),
+            _ => Err(ParseError),
+        }
+    }
+}
+
+/// Direction a beam travels through the manifold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Direction {
+    Down,
+}
+
+/// A beam entering the manifold at a position and travelling in a direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Beam {
+    row: usize,
+    col: usize,
+    dir: Direction,
+}
+
+impl Beam {
+    fn new(row: usize, col: usize, dir: Direction) -> Self {
+        Beam { row, col, dir }
+    }
+}
+
 impl Manifold {
     /// Check if a position contains a splitter
     #[inline]
@@ -65,76 +127,208 @@ impl Manifold {
         (start_row..self.rows).find(|&row| self.is_splitter(row, col))
     }
 
+    /// Number of cells the start beam passes through before reaching the first splitter
+    /// (or the bottom of the grid if its column has none)
+    #[allow(dead_code)]
+    fn initial_run_length(&self) -> usize {
+        let (row, col) = self.start;
+        self.find_next_splitter(row, col).unwrap_or(self.rows) - row
+    }
+
+    /// The beam leaving the start position
+    fn start_beam(&self) -> Beam {
+        Beam::new(self.start.0, self.start.1, Direction::Down)
+    }
+
+    /// Follow a beam until it hits a splitter
+    /// Returns the splitter's (row, col), or None if the beam exits the grid
+    fn trace(&self, beam: Beam) -> Option<(usize, usize)> {
+        match beam.dir {
+            Direction::Down => self
+                .find_next_splitter(beam.row, beam.col)
+                .map(|row| (row, beam.col)),
+        }
+    }
+
+    /// Beams emitted from the `spread` columns to the left and right of a splitter, if
+    /// within the grid (or wrapped around to the opposite edge when `wrap_horizontally`
+    /// is set, in which case narrow grids can receive several beams in one column).
+    /// A beam landing on another splitter on the same row splits again there, so splitters
+    /// that feed each other form a cycle (see `count_quantum_timelines`).
+    fn split(&self, beam: Beam, (row, col): (usize, usize)) -> impl Iterator<Item = Beam> {
+        let spread = self.spread as isize;
+        let targets: Vec<usize> = (-spread..=spread)
+            .filter(|&offset| offset != 0)
+            .filter_map(|offset| {
+                let target = col as isize + offset;
+                if self.wrap_horizontally {
+                    // A one-column grid would wrap back onto the splitter itself
+                    Some(target.rem_euclid(self.cols as isize) as usize).filter(|&c| c != col)
+                } else {
+                    usize::try_from(target).ok().filter(|&c| c < self.cols)
+                }
+            })
+            .collect();
+
+        targets
+            .into_iter()
+            .map(move |col| Beam::new(row, col, beam.dir))
+    }
+
     /// Count beam splits in a classical manifold (Part 1)
     /// Returns the number of unique splitters encountered
     fn count_classical_splits(&self) -> u64 {
+        self.count_classical_splits_from([self.start_beam()], usize::MAX)
+    }
+
+    /// Count beam splits, only following beams through `max_depth` generations of splits
+    /// (the start beam's split is generation one)
+    #[allow(dead_code)]
+    fn count_classical_splits_depth_limited(&self, max_depth: usize) -> u64 {
+        self.count_classical_splits_from([self.start_beam()], max_depth)
+    }
+
+    /// Count beam splits with a source beam entering row 0 at each of the given columns
+    /// Splitters reached by several beams are only counted once
+    #[allow(dead_code)]
+    fn count_classical_splits_multi(&self, starts: &[usize]) -> u64 {
+        self.count_classical_splits_from(
+            starts
+                .iter()
+                .filter(|&&col| col < self.cols)
+                .map(|&col| Beam::new(0, col, Direction::Down)),
+            usize::MAX,
+        )
+    }
+
+    /// How many of the splitters counted by `count_classical_splits` sit in each column
+    #[allow(dead_code)]
+    fn splits_per_column(&self) -> Vec<u64> {
+        let mut counts = vec![0; self.cols];
+        for (_, col) in self.classical_splitters_from([self.start_beam()], usize::MAX) {
+            counts[col] += 1;
+        }
+        counts
+    }
+
+    /// Count the unique splitters hit by beams spreading from the given source beams,
+    /// within `max_depth` generations of splits
+    fn count_classical_splits_from(
+        &self,
+        sources: impl IntoIterator<Item = Beam>,
+        max_depth: usize,
+    ) -> u64 {
+        self.classical_splitters_from(sources, max_depth).len() as u64
+    }
+
+    /// The (row, col) of every splitter hit by beams spreading from the given source
+    /// beams, within `max_depth` generations of splits
+    fn classical_splitters_from(
+        &self,
+        sources: impl IntoIterator<Item = Beam>,
+        max_depth: usize,
+    ) -> HashSet<(usize, usize)> {
         let mut processed_splitters = HashSet::new();
         let mut queue = VecDeque::new();
         let mut seen = HashSet::new();
 
-        // Start with a beam at the starting position
-        queue.push_back((0, self.start_col));
-        seen.insert((0, self.start_col));
+        // Each beam carries how many splits produced it; BFS visits them in that order,
+        // so every splitter is first reached by its earliest generation
+        for start in sources {
+            if seen.insert(start) {
+                queue.push_back((start, 0));
+            }
+        }
+
+        while let Some((beam, generation)) = queue.pop_front() {
+            if generation >= max_depth {
+                continue;
+            }
 
-        while let Some((row, col)) = queue.pop_front() {
-            if let Some(splitter_row) = self.find_next_splitter(row, col) {
+            if let Some(splitter) = self.trace(beam) {
                 // Only process if we haven't seen this splitter before
-                if processed_splitters.insert((splitter_row, col)) {
-                    // Create two new beams from immediate left and right
-                    if col > 0 && seen.insert((splitter_row, col - 1)) {
-                        queue.push_back((splitter_row, col - 1));
-                    }
-                    if col + 1 < self.cols && seen.insert((splitter_row, col + 1)) {
-                        queue.push_back((splitter_row, col + 1));
+                if processed_splitters.insert(splitter) {
+                    for next in self.split(beam, splitter) {
+                        if seen.insert(next) {
+                            queue.push_back((next, generation + 1));
+                        }
                     }
                 }
             }
         }
 
-        processed_splitters.len() as u64
+        processed_splitters
     }
 
     /// Count timelines in a quantum manifold (Part 2)
-    /// Returns the number of distinct paths through the manifold
-    fn count_quantum_timelines(&self) -> u64 {
+    /// Returns the number of distinct paths through the manifold, or None if splitters on
+    /// the same row feed beams back into each other, giving endlessly many timelines
+    fn count_quantum_timelines(&self) -> Option<u64> {
         let mut memo = HashMap::new();
-        self.count_timelines_recursive(0, self.start_col, &mut memo)
+        self.count_timelines_recursive(self.start_beam(), &mut memo)
+    }
+
+    /// For each column, how many quantum timelines leave the bottom of the grid through it,
+    /// or None if the timelines never end (see `count_quantum_timelines`)
+    #[allow(dead_code)]
+    fn exit_distribution(&self) -> Option<Vec<u64>> {
+        let mut memo = HashMap::new();
+        self.exit_distribution_recursive(self.start_beam(), &mut memo)
+    }
+
+    /// Per-column exit counts of the timelines following `beam`, with memoization.
+    /// A beam is memoized as None while its timelines are being followed, so meeting it
+    /// again means a cycle.
+    fn exit_distribution_recursive(
+        &self,
+        beam: Beam,
+        memo: &mut HashMap<Beam, Option<Vec<u64>>>,
+    ) -> Option<Vec<u64>> {
+        if let Some(cached) = memo.get(&beam) {
+            return cached.clone();
+        }
+        memo.insert(beam, None);
+
+        let mut exits = vec![0; self.cols];
+        if let Some(splitter) = self.trace(beam) {
+            for next in self.split(beam, splitter) {
+                let next_exits = self.exit_distribution_recursive(next, memo)?;
+                exits.iter_mut().zip(next_exits).for_each(|(a, b)| *a += b);
+            }
+        } else {
+            exits[beam.col] = 1;
+        }
+
+        memo.insert(beam, Some(exits.clone()));
+        Some(exits)
     }
 
-    /// Recursively count timelines with memoization
+    /// Recursively count timelines with memoization.
+    /// A beam is memoized as None while its timelines are being counted, so meeting it
+    /// again means a cycle.
     fn count_timelines_recursive(
         &self,
-        row: usize,
-        col: usize,
-        memo: &mut HashMap<(usize, usize), u64>,
-    ) -> u64 {
+        beam: Beam,
+        memo: &mut HashMap<Beam, Option<u64>>,
+    ) -> Option<u64> {
         // Check memoization cache
-        if let Some(&cached) = memo.get(&(row, col)) {
+        if let Some(&cached) = memo.get(&beam) {
             return cached;
         }
+        memo.insert(beam, None);
 
-        let result = if let Some(splitter_row) = self.find_next_splitter(row, col) {
+        let result = if let Some(splitter) = self.trace(beam) {
             // Hit a splitter - quantum split into both paths
-            let left_count = if col > 0 {
-                self.count_timelines_recursive(splitter_row, col - 1, memo)
-            } else {
-                0
-            };
-
-            let right_count = if col + 1 < self.cols {
-                self.count_timelines_recursive(splitter_row, col + 1, memo)
-            } else {
-                0
-            };
-
-            left_count + right_count
+            self.split(beam, splitter)
+                .map(|next| self.count_timelines_recursive(next, memo))
+                .sum::<Option<u64>>()?
         } else {
             // Exited the grid - one complete timeline
             1
         };
 
-        memo.insert((row, col), result);
-        result
+        memo.insert(beam, Some(result));
+        Some(result)
     }
 }
 
@@ -145,7 +339,7 @@ pub fn part_one(input: &str) -> Option<u64> {
 
 pub fn part_two(input: &str) -> Option<u64> {
     let manifold = Manifold::try_from(input).ok()?;
-    Some(manifold.count_quantum_timelines())
+    manifold.count_quantum_timelines()
 }
 
 #[cfg(test)]
@@ -163,4 +357,194 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(40));
     }
+
+    #[test]
+    fn test_start_position() {
+        let manifold = Manifold::try_from("...\n.S.\n.^.\n...").unwrap();
+        assert_eq!(manifold.start, (1, 1));
+        assert_eq!(manifold.count_quantum_timelines(), Some(2));
+    }
+
+    #[test]
+    fn test_missing_start() {
+        assert!(Manifold::try_from("...\n.^.\n...").is_err());
+    }
+
+    #[test]
+    fn test_duplicate_start() {
+        assert!(Manifold::try_from(".S.\n.^.\n..S").is_err());
+        assert!(Manifold::try_from("S.S\n.^.\n...").is_err());
+    }
+
+    #[test]
+    fn test_downward_beam_split() {
+        let manifold = Manifold::try_from(".S.\n.^.\n...").unwrap();
+        let beam = manifold.start_beam();
+        assert_eq!(manifold.trace(beam), Some((1, 1)));
+        assert_eq!(
+            manifold.split(beam, (1, 1)).collect::<Vec<_>>(),
+            vec![
+                Beam::new(1, 0, Direction::Down),
+                Beam::new(1, 2, Direction::Down)
+            ]
+        );
+        assert_eq!(manifold.count_classical_splits(), 1);
+        assert_eq!(manifold.count_quantum_timelines(), Some(2));
+    }
+
+    #[test]
+    fn test_downward_beam_split_at_edge() {
+        // The left beam leaves the grid, only the right one continues
+        let manifold = Manifold::try_from("S..\n^..\n...").unwrap();
+        assert_eq!(manifold.count_classical_splits(), 1);
+        assert_eq!(manifold.count_quantum_timelines(), Some(1));
+    }
+
+    #[test]
+    fn test_depth_limited_splits() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let manifold = Manifold::try_from(input.as_str()).unwrap();
+        assert_eq!(manifold.count_classical_splits_depth_limited(0), 0);
+        assert_eq!(manifold.count_classical_splits_depth_limited(1), 1);
+        assert_eq!(manifold.count_classical_splits_depth_limited(2), 3);
+        assert_eq!(
+            manifold.count_classical_splits_depth_limited(usize::MAX),
+            manifold.count_classical_splits()
+        );
+        assert_eq!(manifold.count_classical_splits_depth_limited(100), 21);
+    }
+
+    #[test]
+    fn test_splits_per_column() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let manifold = Manifold::try_from(input.as_str()).unwrap();
+        let profile = manifold.splits_per_column();
+        // Column 9 has three splitters, but no beam reaches the one on row 14
+        assert_eq!(profile, vec![0, 1, 1, 2, 1, 3, 3, 3, 1, 2, 1, 1, 1, 1, 0]);
+        assert_eq!(
+            profile.iter().sum::<u64>(),
+            manifold.count_classical_splits()
+        );
+    }
+
+    #[test]
+    fn test_exit_distribution() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let manifold = Manifold::try_from(input.as_str()).unwrap();
+        let exits = manifold.exit_distribution().unwrap();
+        assert_eq!(exits, vec![1, 0, 2, 0, 10, 0, 11, 0, 11, 0, 2, 1, 1, 0, 1]);
+        assert_eq!(
+            Some(exits.iter().sum::<u64>()),
+            manifold.count_quantum_timelines()
+        );
+    }
+
+    #[test]
+    fn test_multi_start() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let manifold = Manifold::try_from(input.as_str()).unwrap();
+        assert_eq!(
+            manifold.count_classical_splits_multi(&[manifold.start.1]),
+            manifold.count_classical_splits()
+        );
+
+        let manifold = Manifold::try_from("S....\n.....\n^...^\n.....").unwrap();
+        assert_eq!(manifold.count_classical_splits(), 1);
+        assert_eq!(manifold.count_classical_splits_multi(&[0, 4]), 2);
+        assert_eq!(manifold.count_classical_splits_multi(&[0, 0]), 1);
+    }
+
+    #[test]
+    fn test_validate() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        assert!(Manifold::validate(&input).is_ok());
+        assert!(Manifold::validate("...\n.^.\n...").is_err());
+        assert!(Manifold::validate("S.S\n.^.\n...").is_err());
+        assert!(Manifold::validate("").is_err());
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        let bytes = advent_of_code::template::read_file_bytes("examples", DAY);
+        let manifold = Manifold::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(manifold.count_classical_splits(), 21);
+        assert_eq!(manifold.count_quantum_timelines(), Some(40));
+
+        // Bytes that aren't valid UTF-8 are just empty cells
+        let manifold = Manifold::try_from(&b".S\xff\r\n\xfe^.\r\n...\r\n"[..]).unwrap();
+        assert_eq!((manifold.rows, manifold.cols), (3, 3));
+        assert_eq!(manifold.count_quantum_timelines(), Some(2));
+
+        assert!(Manifold::try_from(&b""[..]).is_err());
+    }
+
+    #[test]
+    fn test_initial_run_length() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        assert_eq!(
+            Manifold::try_from(input.as_str())
+                .unwrap()
+                .initial_run_length(),
+            2
+        );
+
+        // No splitter below the start, so the beam runs to the bottom
+        let manifold = Manifold::try_from("...\n.S.\n^.^\n...").unwrap();
+        assert_eq!(manifold.initial_run_length(), 3);
+    }
+
+    #[test]
+    fn test_spread() {
+        let manifold = Manifold {
+            spread: 2,
+            ..Manifold::try_from("..S..\n..^..\n.....").unwrap()
+        };
+        assert_eq!(manifold.count_classical_splits(), 1);
+        assert_eq!(manifold.count_quantum_timelines(), Some(4));
+
+        // The beam two columns right lands on the second splitter, which sends a beam
+        // straight back, so the timelines never end
+        let manifold = Manifold {
+            spread: 2,
+            ..Manifold::try_from(".S...\n.^.^.\n.....").unwrap()
+        };
+        assert_eq!(manifold.count_classical_splits(), 2);
+        assert_eq!(manifold.count_quantum_timelines(), None);
+        assert_eq!(manifold.exit_distribution(), None);
+
+        // The example has splitters two apart on the same row
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let mut manifold = Manifold::try_from(input.as_str()).unwrap();
+        manifold.spread = 2;
+        assert_eq!(manifold.count_classical_splits(), 22);
+        assert_eq!(manifold.count_quantum_timelines(), None);
+    }
+
+    #[test]
+    fn test_adjacent_splitters() {
+        // With the default spread of 1, adjacent splitters feed each other too
+        let manifold = Manifold::try_from("S.\n^^\n..").unwrap();
+        assert_eq!(manifold.spread, 1);
+        assert_eq!(manifold.count_classical_splits(), 2);
+        assert_eq!(manifold.count_quantum_timelines(), None);
+        assert_eq!(part_two("S.\n^^\n.."), None);
+    }
+
+    #[test]
+    fn test_wrap_horizontally() {
+        let mut manifold = Manifold::try_from("S..\n^..\n..^\n...").unwrap();
+        assert_eq!(manifold.count_classical_splits(), 1);
+        assert_eq!(manifold.count_quantum_timelines(), Some(1));
+
+        // The left beam wraps to the last column and hits the second splitter
+        manifold.wrap_horizontally = true;
+        assert_eq!(manifold.count_classical_splits(), 2);
+        assert_eq!(manifold.count_quantum_timelines(), Some(3));
+
+        let single_column = Manifold {
+            wrap_horizontally: true,
+            ..Manifold::try_from("S\n^\n.").unwrap()
+        };
+        assert_eq!(single_column.count_quantum_timelines(), Some(0));
+    }
 }
@@ -1,5 +1,9 @@
 advent_of_code::solution!(8);
 
+use advent_of_code::graph::{DisjointSet, kruskal};
+use advent_of_code::validate::Validate;
+use std::collections::{HashMap, HashSet};
+
 #[derive(Debug)]
 struct ParseError;
 
@@ -14,140 +18,261 @@ impl std::fmt::Display for ParseError {
 
 impl std::error::Error for ParseError {}
 
-struct UnionFind {
-    parent: Vec<usize>,
-    rank: Vec<usize>,
-    num_components: usize,
+/// Distance used to weight edges between junction boxes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Metric {
+    /// Squared Euclidean distance (orders edges the same as Euclidean)
+    #[default]
+    Euclidean,
+    #[allow(dead_code)]
+    Manhattan,
+    #[allow(dead_code)]
+    Chebyshev,
 }
 
-impl UnionFind {
-    fn new(size: usize) -> Self {
-        UnionFind {
-            parent: (0..size).collect(),
-            rank: vec![0; size],
-            num_components: size,
-        }
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Point3D {
+    x: i32,
+    y: i32,
+    z: i32,
+}
 
-    fn find(&mut self, x: usize) -> usize {
-        if self.parent[x] != x {
-            self.parent[x] = self.find(self.parent[x]); // Path compression
+impl Point3D {
+    /// Parse a line the way `try_from` always has: fields that aren't integers are
+    /// dropped, and the line is a box if exactly three remain
+    fn parse_lenient(line: &str) -> Option<Self> {
+        let parts: Vec<i32> = line.split(',').filter_map(|s| s.parse().ok()).collect();
+        match parts[..] {
+            [x, y, z] => Some(Point3D { x, y, z }),
+            _ => None,
         }
-        self.parent[x]
     }
 
-    fn union(&mut self, x: usize, y: usize) -> bool {
-        let root_x = self.find(x);
-        let root_y = self.find(y);
-
-        if root_x == root_y {
-            return false; // Already in same set
-        }
+    /// Parse an `x,y,z` line
+    fn parse(line: &str) -> Result<Self, String> {
+        let parts = line
+            .split(',')
+            .map(|s| s.trim().parse::<i32>().map_err(|e| format!("{s:?}: {e}")))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        if self.rank[root_x] < self.rank[root_y] {
-            self.parent[root_x] = root_y;
-        } else if self.rank[root_x] > self.rank[root_y] {
-            self.parent[root_y] = root_x;
-        } else {
-            self.parent[root_y] = root_x;
-            self.rank[root_x] += 1;
+        match parts[..] {
+            [x, y, z] => Ok(Point3D { x, y, z }),
+            _ => Err(format!("expected 3 coordinates, found {}", parts.len())),
         }
-
-        self.num_components -= 1;
-        true
     }
 
     #[inline]
-    fn component_count(&self) -> usize {
-        self.num_components
+    fn distance_squared(&self, other: &Point3D) -> i64 {
+        let dx = (self.x - other.x) as i64;
+        let dy = (self.y - other.y) as i64;
+        let dz = (self.z - other.z) as i64;
+        dx * dx + dy * dy + dz * dz
     }
 
-    fn get_component_sizes(&mut self) -> Vec<usize> {
-        let n = self.parent.len();
-        let mut sizes = vec![0; n];
-
-        for i in 0..n {
-            let root = self.find(i);
-            sizes[root] += 1;
+    #[inline]
+    fn distance(&self, other: &Point3D, metric: Metric) -> i64 {
+        match metric {
+            Metric::Euclidean => self.distance_squared(other),
+            Metric::Manhattan | Metric::Chebyshev => {
+                let deltas = [
+                    (self.x - other.x).abs() as i64,
+                    (self.y - other.y).abs() as i64,
+                    (self.z - other.z).abs() as i64,
+                ];
+                if metric == Metric::Manhattan {
+                    deltas.iter().sum()
+                } else {
+                    deltas.into_iter().max().unwrap()
+                }
+            }
         }
-
-        sizes.into_iter().filter(|&s| s > 0).collect()
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Point3D {
-    x: i32,
-    y: i32,
-    z: i32,
-}
+/// Secondary edge ordering applied between boxes at equal distance. Edges it also ties
+/// fall back to the sum of their box indices, then the lower index.
+type TieBreak = fn(&Point3D, &Point3D) -> i64;
 
-impl Point3D {
-    #[inline]
-    fn distance_squared(&self, other: &Point3D) -> i64 {
-        let dx = (self.x - other.x) as i64;
-        let dy = (self.y - other.y) as i64;
-        let dz = (self.z - other.z) as i64;
-        dx * dx + dy * dy + dz * dz
-    }
+/// Ties every edge, leaving equal distances to the index fallback alone
+fn no_tie_break(_: &Point3D, _: &Point3D) -> i64 {
+    0
 }
 
 struct JunctionNetwork {
     boxes: Vec<Point3D>,
-    edges: Vec<(i64, usize, usize)>,
+    edges: Vec<(u64, usize, usize)>,
 }
 
 impl TryFrom<&str> for JunctionNetwork {
     type Error = ParseError;
 
     fn try_from(input: &str) -> Result<Self, Self::Error> {
+        Self::parse_with_metric(input, Metric::default())
+    }
+}
+
+impl Validate for JunctionNetwork {
+    type Error = ParseError;
+
+    /// Every non-blank line must be an `x,y,z` box, without building any edges
+    fn validate(input: &str) -> Result<(), Self::Error> {
+        let mut boxes = 0;
+        for line in input.lines().filter(|line| !line.trim().is_empty()) {
+            Point3D::parse(line).map_err(|_| ParseError)?;
+            boxes += 1;
+        }
+
+        if boxes == 0 {
+            return Err(ParseError);
+        }
+        Ok(())
+    }
+}
+
+impl JunctionNetwork {
+    /// Parse the network, weighting edges with the given distance metric
+    fn parse_with_metric(input: &str, metric: Metric) -> Result<Self, ParseError> {
+        Self::parse_with(input, metric, no_tie_break, false)
+    }
+
+    /// Parse the network, ordering equal-distance edges by `tie_break`
+    #[allow(dead_code)]
+    fn parse_with_tie_break(input: &str, tie_break: TieBreak) -> Result<Self, ParseError> {
+        Self::parse_with(input, Metric::default(), tie_break, false)
+    }
+
+    /// Parse the network with every option spelled out. With `dedup`, boxes at the same
+    /// coordinates collapse into a single box (the first), so they don't get a zero-length
+    /// edge and each position counts once towards its circuit's size.
+    fn parse_with(
+        input: &str,
+        metric: Metric,
+        tie_break: TieBreak,
+        dedup: bool,
+    ) -> Result<Self, ParseError> {
+        let mut seen = HashSet::new();
         let boxes: Vec<Point3D> = input
             .lines()
-            .filter_map(|line| {
-                let parts: Vec<i32> = line.split(',').filter_map(|s| s.parse().ok()).collect();
-                if parts.len() == 3 {
-                    Some(Point3D {
-                        x: parts[0],
-                        y: parts[1],
-                        z: parts[2],
-                    })
-                } else {
-                    None
-                }
-            })
+            .filter_map(Point3D::parse_lenient)
+            .filter(|&point| !dedup || seen.insert(point))
             .collect();
 
         if boxes.is_empty() {
             return Err(ParseError);
         }
 
+        Ok(Self::from_boxes(boxes, metric, tie_break))
+    }
+
+    /// Parse like `try_from`, but report every malformed line as (line index, message)
+    /// instead of silently skipping it
+    #[allow(dead_code)]
+    fn try_from_strict(input: &str) -> Result<Self, Vec<(usize, String)>> {
+        let mut boxes = Vec::new();
+        let mut errors = Vec::new();
+
+        for (idx, line) in input.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match Point3D::parse(line) {
+                Ok(point) => boxes.push(point),
+                Err(message) => errors.push((idx, message)),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        if boxes.is_empty() {
+            return Err(vec![(0, "no junction boxes found".to_string())]);
+        }
+
+        Ok(Self::from_boxes(boxes, Metric::default(), no_tie_break))
+    }
+
+    /// Build the network with all pairwise edges sorted by distance
+    ///
+    /// Equal distances are ordered by `tie_break`, then by the sum of the box indices and
+    /// finally the lower index, so the edge order (and every answer built on it) is fully
+    /// deterministic.
+    fn from_boxes(boxes: Vec<Point3D>, metric: Metric, tie_break: TieBreak) -> Self {
         let n = boxes.len();
         let mut edges = Vec::with_capacity(n * (n - 1) / 2);
 
         for i in 0..n {
             for j in i + 1..n {
-                let dist_sq = boxes[i].distance_squared(&boxes[j]);
-                edges.push((dist_sq, i, j));
+                let dist = boxes[i].distance(&boxes[j], metric);
+                // Every metric is non-negative
+                edges.push((dist as u64, i, j));
             }
         }
 
-        edges.sort_unstable_by_key(|&(dist, _, _)| dist);
+        edges.sort_unstable_by_key(|&(dist, i, j)| {
+            (dist, tie_break(&boxes[i], &boxes[j]), i + j, i)
+        });
 
-        Ok(JunctionNetwork { boxes, edges })
+        JunctionNetwork { boxes, edges }
     }
 }
 
+/// Summary of a network for reporting, from `JunctionNetwork::analyze`
+#[derive(Debug, PartialEq, Eq)]
+struct NetworkStats {
+    /// Circuits left after connecting the k closest pairs
+    component_count: usize,
+    /// `connect_k_closest`'s answer for the same k
+    largest_three_product: Option<u64>,
+    /// Total edge distance of the minimum spanning tree (or forest, if never connected)
+    total_mst_weight: u64,
+    /// `completing_edge`'s answer
+    completing_edge: Option<(usize, usize, u64)>,
+}
+
 impl JunctionNetwork {
+    /// Apply the edge at `edge_index` in sorted order to a caller-held union-find
+    /// Returns whether it merged two components (false if the index is out of range)
+    fn step_connect(&self, uf: &mut DisjointSet, edge_index: usize) -> bool {
+        self.edges
+            .get(edge_index)
+            .is_some_and(|&(_, u, v)| uf.union(u, v))
+    }
+
     fn connect_k_closest(&self, k: usize) -> Option<u64> {
-        let mut uf = UnionFind::new(self.boxes.len());
+        let mut uf = DisjointSet::new(self.boxes.len());
         let connections = k.min(self.edges.len());
 
         for i in 0..connections {
-            let (_, u, v) = self.edges[i];
-            uf.union(u, v);
+            self.step_connect(&mut uf, i);
         }
 
-        let mut sizes = uf.get_component_sizes();
+        Self::top3_product(&mut uf)
+    }
+
+    /// After connecting the `k` closest pairs, one `x,y,z,cluster_id` line per box, with
+    /// cluster ids numbered from 0 in order of each cluster's first box
+    #[allow(dead_code)]
+    fn export_clusters(&self, k: usize) -> String {
+        let mut uf = DisjointSet::new(self.boxes.len());
+        for i in 0..k.min(self.edges.len()) {
+            self.step_connect(&mut uf, i);
+        }
+
+        let mut cluster_ids = HashMap::new();
+        self.boxes
+            .iter()
+            .enumerate()
+            .map(|(idx, b)| {
+                let next_id = cluster_ids.len();
+                let cluster_id = *cluster_ids.entry(uf.find(idx)).or_insert(next_id);
+                format!("{},{},{},{cluster_id}\n", b.x, b.y, b.z)
+            })
+            .collect()
+    }
+
+    /// Product of the three largest component sizes, or None if there are fewer than three
+    fn top3_product(uf: &mut DisjointSet) -> Option<u64> {
+        let mut sizes = uf.component_sizes();
         sizes.sort_unstable_by(|a, b| b.cmp(a));
 
         if sizes.len() >= 3 {
@@ -157,18 +282,98 @@ impl JunctionNetwork {
         }
     }
 
+    /// `connect_k_closest` for each k in `k_values`, built from a single union-find that
+    /// is extended edge by edge through the requested k in ascending order
+    #[allow(dead_code)]
+    fn top3_product_curve(&self, k_values: &[usize]) -> Vec<Option<u64>> {
+        let mut order: Vec<usize> = (0..k_values.len()).collect();
+        order.sort_unstable_by_key(|&idx| k_values[idx]);
+
+        let mut uf = DisjointSet::new(self.boxes.len());
+        let mut connected = 0;
+        let mut curve = vec![None; k_values.len()];
+
+        for idx in order {
+            let k = k_values[idx].min(self.edges.len());
+            while connected < k {
+                self.step_connect(&mut uf, connected);
+                connected += 1;
+            }
+            curve[idx] = Self::top3_product(&mut uf);
+        }
+
+        curve
+    }
+
     fn connect_until_single_circuit(&self) -> Option<u64> {
-        let mut uf = UnionFind::new(self.boxes.len());
+        let (u, v, _) = self.completing_edge()?;
+        Some((self.boxes[u].x as u64) * (self.boxes[v].x as u64))
+    }
+
+    /// The `(u, v, dist)` edge whose connection joins the last two circuits, or None if
+    /// the boxes never form a single circuit.
+    ///
+    /// That is the last edge Kruskal's algorithm adds to the minimum spanning tree.
+    fn completing_edge(&self) -> Option<(usize, usize, u64)> {
+        let tree = kruskal(self.boxes.len(), &self.edges);
+        if tree.len() + 1 != self.boxes.len() {
+            return None;
+        }
 
-        for &(_, u, v) in &self.edges {
-            uf.union(u, v);
+        let &(u, v) = tree.last()?;
+        self.edges
+            .iter()
+            .find(|&&(_, a, b)| (a, b) == (u, v))
+            .map(|&(dist, u, v)| (u, v, dist))
+    }
 
-            if uf.component_count() == 1 {
-                return Some((self.boxes[u].x as u64) * (self.boxes[v].x as u64));
+    /// Gather `NetworkStats` for the `k` closest pairs with one union-find, extended edge
+    /// by edge through k and then on until every box is connected
+    #[allow(dead_code)]
+    fn analyze(&self, k: usize) -> NetworkStats {
+        let mut uf = DisjointSet::new(self.boxes.len());
+        let mut at_k = None;
+        let mut total_mst_weight = 0;
+        let mut completing_edge = None;
+
+        for (i, &(dist, u, v)) in self.edges.iter().enumerate() {
+            if i == k {
+                at_k = Some((uf.component_count(), Self::top3_product(&mut uf)));
+            }
+            if at_k.is_some() && uf.component_count() == 1 {
+                break;
             }
+
+            if self.step_connect(&mut uf, i) {
+                total_mst_weight += dist;
+                if uf.component_count() == 1 {
+                    completing_edge = Some((u, v, dist));
+                }
+            }
+        }
+
+        // k may cover every edge
+        let (component_count, largest_three_product) =
+            at_k.unwrap_or_else(|| (uf.component_count(), Self::top3_product(&mut uf)));
+
+        NetworkStats {
+            component_count,
+            largest_three_product,
+            total_mst_weight,
+            completing_edge,
+        }
+    }
+
+    /// Like `connect_until_single_circuit`, but gives up after the `m` closest edges
+    #[allow(dead_code)]
+    fn becomes_connected_within(&self, m: usize) -> Option<u64> {
+        let tree = kruskal(self.boxes.len(), &self.edges[..m.min(self.edges.len())]);
+        if tree.len() + 1 != self.boxes.len() {
+            return None;
         }
 
-        None
+        let &(u, v) = tree.last()?;
+        Some((self.boxes[u].x as u64) * (self.boxes[v].x as u64))
     }
 }
 
@@ -201,4 +406,197 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(25272));
     }
+
+    #[test]
+    fn test_becomes_connected_within() {
+        let network = JunctionNetwork::try_from(
+            advent_of_code::template::read_file("examples", DAY).as_str(),
+        )
+        .unwrap();
+        assert_eq!(network.becomes_connected_within(10), None);
+        assert_eq!(network.becomes_connected_within(28), None);
+        assert_eq!(network.becomes_connected_within(29), Some(25272));
+    }
+
+    #[test]
+    fn test_step_connect() {
+        let network = JunctionNetwork::try_from(
+            advent_of_code::template::read_file("examples", DAY).as_str(),
+        )
+        .unwrap();
+        let mut uf = DisjointSet::new(network.boxes.len());
+        assert_eq!(uf.component_count(), 20);
+
+        // The fourth closest pair is already joined through the first three
+        let merged: Vec<bool> = (0..4).map(|i| network.step_connect(&mut uf, i)).collect();
+        assert_eq!(merged, vec![true, true, true, false]);
+        assert_eq!(uf.component_count(), 17);
+
+        assert!(!network.step_connect(&mut uf, network.edges.len()));
+    }
+
+    #[test]
+    fn test_top3_product_curve() {
+        let network = JunctionNetwork::try_from(
+            advent_of_code::template::read_file("examples", DAY).as_str(),
+        )
+        .unwrap();
+        let curve = network.top3_product_curve(&[10, 5, 1000]);
+        assert_eq!(curve, vec![Some(40), Some(12), None]);
+        assert_eq!(curve[1], network.connect_k_closest(5));
+    }
+
+    #[test]
+    fn test_export_clusters() {
+        let network = JunctionNetwork::try_from(
+            advent_of_code::template::read_file("examples", DAY).as_str(),
+        )
+        .unwrap();
+        let export = network.export_clusters(10);
+
+        let rows: Vec<Vec<i64>> = export
+            .lines()
+            .map(|line| line.split(',').map(|v| v.parse().unwrap()).collect())
+            .collect();
+        assert_eq!(rows.len(), network.boxes.len());
+        assert_eq!(rows[0][3], 0);
+
+        let mut sizes: HashMap<i64, usize> = HashMap::new();
+        for (idx, row) in rows.iter().enumerate() {
+            let b = network.boxes[idx];
+            assert_eq!(&row[..3], &[b.x as i64, b.y as i64, b.z as i64]);
+            *sizes.entry(row[3]).or_default() += 1;
+        }
+
+        // Ids are dense and the clusters match connect_k_closest's components
+        assert_eq!(sizes.len(), 11);
+        assert!((0..11).all(|id| sizes.contains_key(&id)));
+        let mut sizes: Vec<usize> = sizes.into_values().collect();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        assert_eq!(&sizes[..3], &[5, 4, 2]);
+    }
+
+    #[test]
+    fn test_completing_edge() {
+        let network = JunctionNetwork::try_from(
+            advent_of_code::template::read_file("examples", DAY).as_str(),
+        )
+        .unwrap();
+
+        // 216,146,977 and 117,168,530 are 99, 22 and 447 apart
+        assert_eq!(
+            network.completing_edge(),
+            Some((10, 12, 99 * 99 + 22 * 22 + 447 * 447))
+        );
+
+        let pair = JunctionNetwork::try_from("0,0,0\n1,0,0\n").unwrap();
+        assert_eq!(pair.completing_edge(), Some((0, 1, 1)));
+        let single = JunctionNetwork::try_from("0,0,0\n").unwrap();
+        assert_eq!(single.completing_edge(), None);
+    }
+
+    #[test]
+    fn test_analyze() {
+        let network = JunctionNetwork::try_from(
+            advent_of_code::template::read_file("examples", DAY).as_str(),
+        )
+        .unwrap();
+        let stats = network.analyze(10);
+        assert_eq!(
+            stats,
+            NetworkStats {
+                component_count: 11,
+                largest_three_product: Some(40),
+                total_mst_weight: 2596246,
+                completing_edge: Some((10, 12, 210094)),
+            }
+        );
+
+        let tree_weight: u64 = kruskal(network.boxes.len(), &network.edges)
+            .iter()
+            .map(|&(u, v)| network.boxes[u].distance(&network.boxes[v], Metric::default()) as u64)
+            .sum();
+        assert_eq!(stats.total_mst_weight, tree_weight);
+        assert_eq!(stats.completing_edge, network.completing_edge());
+
+        // Past the last edge, k only affects the circuit counts
+        let all_edges = network.analyze(1000);
+        assert_eq!(all_edges.component_count, 1);
+        assert_eq!(all_edges.largest_three_product, None);
+        assert_eq!(all_edges.total_mst_weight, stats.total_mst_weight);
+    }
+
+    #[test]
+    fn test_dedup_coincident_boxes() {
+        let input = "0,0,0\n0,0,0\n5,0,0\n20,0,0\n0,0,0\n";
+        let network = JunctionNetwork::try_from(input).unwrap();
+        assert_eq!(network.boxes.len(), 5);
+        // The two zero-length edges are connected first
+        assert_eq!(network.edges[0].0, 0);
+        assert_eq!(network.analyze(2).component_count, 3);
+
+        let deduped =
+            JunctionNetwork::parse_with(input, Metric::default(), no_tie_break, true).unwrap();
+        assert_eq!(deduped.boxes.len(), 3);
+        assert_eq!(deduped.edges[0], (25, 0, 1));
+        assert_eq!(deduped.analyze(0).component_count, 3);
+        assert_eq!(deduped.analyze(2).component_count, 1);
+    }
+
+    #[test]
+    fn test_metric() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let manhattan = JunctionNetwork::parse_with_metric(&input, Metric::Manhattan).unwrap();
+        assert_eq!(manhattan.connect_k_closest(10), Some(36));
+
+        let euclidean = JunctionNetwork::parse_with_metric(&input, Metric::Euclidean).unwrap();
+        assert_eq!(euclidean.connect_k_closest(10), Some(40));
+
+        let (a, b) = (Point3D { x: 0, y: 0, z: 0 }, Point3D { x: 1, y: -2, z: 3 });
+        assert_eq!(a.distance(&b, Metric::Euclidean), 14);
+        assert_eq!(a.distance(&b, Metric::Manhattan), 6);
+        assert_eq!(a.distance(&b, Metric::Chebyshev), 3);
+    }
+
+    #[test]
+    fn test_tie_break() {
+        // Boxes evenly spaced on a line, so every neighboring pair is at distance 1
+        let input = "1,0,0\n2,0,0\n3,0,0\n4,0,0\n";
+        for _ in 0..3 {
+            assert_eq!(part_two(input), Some(12));
+        }
+
+        let farthest_first = |a: &Point3D, b: &Point3D| -i64::from(a.x + b.x);
+        let network = JunctionNetwork::parse_with_tie_break(input, farthest_first).unwrap();
+        assert_eq!(network.connect_until_single_circuit(), Some(2));
+    }
+
+    #[test]
+    fn test_validate() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        assert!(JunctionNetwork::validate(&input).is_ok());
+        assert!(JunctionNetwork::validate("1,2,3\n4,5\n").is_err());
+        assert!(JunctionNetwork::validate("\n\n").is_err());
+    }
+
+    #[test]
+    fn test_try_from_strict_reports_all_errors() {
+        let input = "1,2,3\n4,5\n\n7,x,9\n10,11,12\n1,2,3,4\n";
+        let errors = JunctionNetwork::try_from_strict(input).err().unwrap();
+        let lines: Vec<usize> = errors.iter().map(|&(idx, _)| idx).collect();
+        assert_eq!(lines, vec![1, 3, 5]);
+
+        // The lenient parser skips the same lines
+        assert_eq!(JunctionNetwork::try_from(input).unwrap().boxes.len(), 2);
+        assert!(JunctionNetwork::try_from_strict("1,2,3\n4,5,6\n").is_ok());
+
+        // A stray field is dropped by the lenient parser but rejected by the strict one
+        let input = "1,x,2,3\n4,5,6\n";
+        let network = JunctionNetwork::try_from(input).unwrap();
+        assert_eq!(network.boxes[0], Point3D { x: 1, y: 2, z: 3 });
+        assert_eq!(
+            JunctionNetwork::try_from_strict(input).err().unwrap()[0].0,
+            0
+        );
+    }
 }
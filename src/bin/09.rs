@@ -1,5 +1,13 @@
 advent_of_code::solution!(9);
 
+use advent_of_code::geometry::{Point2D as Point, scanline_fill};
+use advent_of_code::validate::Validate;
+use std::collections::{HashMap, HashSet};
+
+/// For an x-span, indexed by row offset from `min_y`: the offset of the first row at or
+/// below it where the span is not valid (the row count if there is none)
+type SpanRuns = Vec<usize>;
+
 #[derive(Debug)]
 struct ParseError;
 
@@ -11,12 +19,6 @@ impl std::fmt::Display for ParseError {
 
 impl std::error::Error for ParseError {}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Point {
-    x: i32,
-    y: i32,
-}
-
 struct TileGrid {
     tiles: Vec<Point>,
     valid_ranges: Vec<Vec<(i32, i32)>>,
@@ -43,121 +45,207 @@ impl TryFrom<&str> for TileGrid {
             return Err(ParseError);
         }
 
-        let min_y = tiles.iter().map(|p| p.y).min().unwrap();
-        let max_y = tiles.iter().map(|p| p.y).max().unwrap();
-
-        let (valid_ranges, min_y_actual, max_y_actual) =
-            Self::compute_valid_ranges(&tiles, min_y, max_y);
+        // Unordered point clouds are sorted into a boundary so the fill sees valid edges.
+        // That only works for convex or star-shaped clouds, so anything still left with a
+        // diagonal edge is rejected rather than filled as a bogus polygon.
+        let rows = if forms_polygon(&tiles) {
+            scanline_fill(&tiles)
+        } else {
+            let ordered = angular_order(&tiles);
+            if !forms_polygon(&ordered) {
+                return Err(ParseError);
+            }
+            scanline_fill(&ordered)
+        };
+        let min_y = rows.first().map(|&(y, _)| y).unwrap();
+        let max_y = rows.last().map(|&(y, _)| y).unwrap();
+        let valid_ranges = rows.into_iter().map(|(_, ranges)| ranges).collect();
 
         Ok(TileGrid {
             tiles,
             valid_ranges,
-            min_y: min_y_actual,
-            max_y: max_y_actual,
+            min_y,
+            max_y,
         })
     }
 }
 
-impl TileGrid {
-    /// Compute valid x-ranges for each y-coordinate using a scanline algorithm
-    ///
-    /// For each horizontal line y:
-    /// 1. Find all vertical edges that cross this line
-    /// 2. Sort crossing points and pair them to create valid ranges
-    /// 3. Include any horizontal edges at this y-coordinate
-    /// 4. Merge overlapping ranges
-    ///
-    /// Returns (ranges_vec, min_y, max_y) where ranges_vec[i] corresponds to y = min_y + i
-    fn compute_valid_ranges(
-        tiles: &[Point],
-        min_y: i32,
-        max_y: i32,
-    ) -> (Vec<Vec<(i32, i32)>>, i32, i32) {
-        let n = tiles.len();
-        let height = (max_y - min_y + 1) as usize;
-        let mut ranges = vec![Vec::new(); height];
+impl Validate for TileGrid {
+    type Error = ParseError;
 
-        for y in min_y..=max_y {
-            let y_idx = (y - min_y) as usize;
-            let mut crossings = Vec::new();
+    /// Every non-empty line must be an `x,y` pair, without filling the polygon
+    fn validate(input: &str) -> Result<(), Self::Error> {
+        let mut tiles = 0;
+        for line in input.lines().filter(|line| !line.is_empty()) {
+            let (x, y) = line.split_once(',').ok_or(ParseError)?;
+            if x.parse::<i32>().is_err() || y.parse::<i32>().is_err() {
+                return Err(ParseError);
+            }
+            tiles += 1;
+        }
+
+        if tiles == 0 {
+            return Err(ParseError);
+        }
+        Ok(())
+    }
+}
 
-            for i in 0..n {
-                let p1 = tiles[i];
-                let p2 = tiles[(i + 1) % n];
+/// Whether consecutive tiles (wrapping around) always share a row or column,
+/// i.e. the input already lists the corners of a rectilinear polygon in order
+fn forms_polygon(tiles: &[Point]) -> bool {
+    let n = tiles.len();
+    (0..n).all(|i| {
+        let (p1, p2) = (tiles[i], tiles[(i + 1) % n]);
+        p1.x == p2.x || p1.y == p2.y
+    })
+}
 
-                // Check for vertical edges crossing this horizontal line
-                if p1.x == p2.x && ((p1.y <= y && y < p2.y) || (p2.y <= y && y < p1.y)) {
-                    crossings.push(p1.x);
-                }
+/// Sort points by angle around their centroid, breaking ties by distance from it
+fn angular_order(points: &[Point]) -> Vec<Point> {
+    let n = points.len() as f64;
+    let cx = points.iter().map(|p| f64::from(p.x)).sum::<f64>() / n;
+    let cy = points.iter().map(|p| f64::from(p.y)).sum::<f64>() / n;
+    let key = |p: &Point| {
+        let (dx, dy) = (f64::from(p.x) - cx, f64::from(p.y) - cy);
+        (dy.atan2(dx), dx.hypot(dy))
+    };
+
+    let mut ordered = points.to_vec();
+    ordered.sort_by(|a, b| {
+        let (ka, kb) = (key(a), key(b));
+        ka.0.total_cmp(&kb.0).then(ka.1.total_cmp(&kb.1))
+    });
+    ordered
+}
 
-                // Check for horizontal edges at this y-coordinate
-                if p1.y == p2.y && p1.y == y {
-                    let x_min = p1.x.min(p2.x);
-                    let x_max = p1.x.max(p2.x);
-                    ranges[y_idx].push((x_min, x_max));
-                }
-            }
+impl TileGrid {
+    /// Like `try_from`, but rejects inputs that list any tile more than once
+    #[allow(dead_code)]
+    fn try_from_strict(input: &str) -> Result<Self, ParseError> {
+        let grid = Self::try_from(input)?;
+        if !grid.duplicate_tiles().is_empty() {
+            return Err(ParseError);
+        }
+        Ok(grid)
+    }
 
-            // Create ranges from vertical edge crossings (inside the polygon)
-            crossings.sort_unstable();
-            for chunk in crossings.chunks(2) {
-                if chunk.len() == 2 {
-                    ranges[y_idx].push((chunk[0], chunk[1]));
-                }
-            }
+    /// Tiles listed more than once, each reported once in order of its first repeat
+    fn duplicate_tiles(&self) -> Vec<Point> {
+        let mut seen = HashSet::new();
+        let mut reported = HashSet::new();
+        self.tiles
+            .iter()
+            .filter(|&&tile| !seen.insert(tile) && reported.insert(tile))
+            .copied()
+            .collect()
+    }
 
-            // Merge overlapping ranges for this y-coordinate
-            Self::merge_ranges(&mut ranges[y_idx]);
+    /// The tiles in boundary order: input order if it already forms a polygon,
+    /// otherwise sorted by angle around the centroid
+    #[allow(dead_code)]
+    fn ordered_boundary(&self) -> Vec<Point> {
+        if forms_polygon(&self.tiles) {
+            self.tiles.clone()
+        } else {
+            angular_order(&self.tiles)
         }
+    }
 
-        (ranges, min_y, max_y)
+    /// Length of the boundary in grid units, walking the same edges the scanline fill uses
+    #[allow(dead_code)]
+    fn perimeter(&self) -> u64 {
+        let boundary = self.ordered_boundary();
+        let n = boundary.len();
+        (0..n)
+            .map(|i| {
+                let (from, to) = (boundary[i], boundary[(i + 1) % n]);
+                from.x.abs_diff(to.x) as u64 + from.y.abs_diff(to.y) as u64
+            })
+            .sum()
+    }
+
+    /// For each row from `min_y` upward, the row's y-coordinate and how many x-cells are valid
+    #[allow(dead_code)]
+    fn valid_span_per_row(&self) -> Vec<(i32, u64)> {
+        self.valid_ranges
+            .iter()
+            .enumerate()
+            .map(|(y_idx, ranges)| {
+                let count = ranges
+                    .iter()
+                    .map(|&(start, end)| (end - start) as u64 + 1)
+                    .sum();
+                (self.min_y + y_idx as i32, count)
+            })
+            .collect()
     }
 
-    /// Merge overlapping or adjacent ranges in-place
-    fn merge_ranges(ranges: &mut Vec<(i32, i32)>) {
-        if ranges.is_empty() {
-            return;
+    /// Find the largest rectangle using any two red tiles as opposite corners
+    fn largest_rectangle_area(&self) -> u64 {
+        let n = self.tiles.len();
+        let mut max_area = 0u64;
+
+        for i in 0..n {
+            for j in i + 1..n {
+                let (width, height) = self.dimensions(i, j);
+                max_area = max_area.max(width * height);
+            }
         }
 
-        ranges.sort_unstable();
-        let mut merged: Vec<(i32, i32)> = Vec::new();
-
-        for &(start, end) in ranges.iter() {
-            if let Some(last) = merged.last_mut() {
-                if start <= last.1 {
-                    // Overlapping or adjacent - extend the last range
-                    last.1 = last.1.max(end);
-                } else {
-                    // Non-overlapping - add new range
-                    merged.push((start, end));
+        max_area
+    }
+
+    /// Largest rectangle whose four corners are all red tiles, ignoring validity.
+    /// Pairs on the same row or column don't span a rectangle with four distinct corners,
+    /// so they are skipped.
+    #[allow(dead_code)]
+    fn largest_red_corner_rectangle(&self) -> u64 {
+        let red: HashSet<Point> = self.tiles.iter().copied().collect();
+        let n = self.tiles.len();
+        let mut max_area = 0u64;
+
+        for i in 0..n {
+            for j in i + 1..n {
+                let (p1, p2) = (self.tiles[i], self.tiles[j]);
+                if p1.x == p2.x || p1.y == p2.y {
+                    continue;
+                }
+
+                if red.contains(&Point { x: p1.x, y: p2.y })
+                    && red.contains(&Point { x: p2.x, y: p1.y })
+                {
+                    let (width, height) = self.dimensions(i, j);
+                    max_area = max_area.max(width * height);
                 }
-            } else {
-                merged.push((start, end));
             }
         }
 
-        *ranges = merged;
+        max_area
     }
 
-    /// Find the largest rectangle using any two red tiles as opposite corners
-    fn largest_rectangle_area(&self) -> u64 {
+    /// Number of tile pairs whose rectangle has the largest area, ignoring validity
+    #[allow(dead_code)]
+    fn count_max_area_rectangles(&self) -> usize {
         let n = self.tiles.len();
         let mut max_area = 0u64;
+        let mut count = 0;
 
         for i in 0..n {
             for j in i + 1..n {
-                let p1 = self.tiles[i];
-                let p2 = self.tiles[j];
-
-                let width = (p1.x - p2.x).unsigned_abs() as u64 + 1;
-                let height = (p1.y - p2.y).unsigned_abs() as u64 + 1;
+                let (width, height) = self.dimensions(i, j);
                 let area = width * height;
 
-                max_area = max_area.max(area);
+                if area > max_area {
+                    max_area = area;
+                    count = 1;
+                } else if area == max_area {
+                    count += 1;
+                }
             }
         }
 
-        max_area
+        count
     }
 
     /// Check if the x-range [x1, x2] is entirely within valid ranges for y
@@ -192,41 +280,130 @@ impl TileGrid {
         true
     }
 
-    /// Find the largest valid rectangle (all tiles red or green)
-    fn largest_valid_rectangle_area(&self) -> u64 {
+    /// Every pair of tiles as (area, i, j), ordered by `(Reverse(area), i, j)`
+    fn sorted_candidates(&self) -> Vec<(u64, usize, usize)> {
         let n = self.tiles.len();
 
         // Pre-compute all candidate areas with their indices
         let mut candidates: Vec<(u64, usize, usize)> = (0..n)
             .flat_map(|i| {
                 (i + 1..n).map(move |j| {
-                    let p1 = self.tiles[i];
-                    let p2 = self.tiles[j];
-                    let width = (p1.x - p2.x).unsigned_abs() as u64 + 1;
-                    let height = (p1.y - p2.y).unsigned_abs() as u64 + 1;
-                    let area = width * height;
-                    (area, i, j)
+                    let (width, height) = self.dimensions(i, j);
+                    (width * height, i, j)
                 })
             })
             .collect();
 
-        // Sort by area descending
-        candidates.sort_unstable_by_key(|&(area, _, _)| std::cmp::Reverse(area));
+        // Sort by area descending, breaking ties by index for a deterministic choice
+        candidates.sort_unstable_by_key(|&(area, i, j)| (std::cmp::Reverse(area), i, j));
+        candidates
+    }
+
+    /// Width and height of the rectangle with tiles i and j as opposite corners
+    #[inline]
+    fn dimensions(&self, i: usize, j: usize) -> (u64, u64) {
+        let p1 = self.tiles[i];
+        let p2 = self.tiles[j];
+        let width = (p1.x - p2.x).unsigned_abs() as u64 + 1;
+        let height = (p1.y - p2.y).unsigned_abs() as u64 + 1;
+        (width, height)
+    }
 
+    /// Find the largest valid rectangle (all tiles red or green)
+    ///
+    /// Candidates are ordered by `(Reverse(area), i, j)`, so among equal-area valid
+    /// rectangles the one with the lowest tile indices is always chosen.
+    /// Returns (area, i, j) where i and j index the opposite corner tiles.
+    fn largest_valid_rectangle(&self) -> Option<(u64, usize, usize)> {
         // Check candidates in order of decreasing area
-        for &(area, i, j) in &candidates {
-            let p1 = self.tiles[i];
-            let p2 = self.tiles[j];
+        self.sorted_candidates()
+            .into_iter()
+            .find(|&(_, i, j)| self.is_valid_rectangle(self.tiles[i], self.tiles[j]))
+    }
 
-            if self.is_valid_rectangle(p1, p2) {
-                return area;
-            }
+    /// Compute the contiguous valid-row runs of the x-range [x1, x2]
+    fn span_runs(&self, x1: i32, x2: i32) -> SpanRuns {
+        let rows = self.valid_ranges.len();
+        let mut runs = vec![rows; rows + 1];
+
+        for y_idx in (0..rows).rev() {
+            runs[y_idx] = if self.is_x_range_valid(self.min_y + y_idx as i32, x1, x2) {
+                runs[y_idx + 1]
+            } else {
+                y_idx
+            };
+        }
+
+        runs
+    }
+
+    /// Like `is_valid_rectangle`, but answers from per-span valid-row runs that are
+    /// computed once per distinct x-span and kept in `cache`
+    fn is_valid_rectangle_cached(
+        &self,
+        p1: Point,
+        p2: Point,
+        cache: &mut HashMap<(i32, i32), SpanRuns>,
+    ) -> bool {
+        let (min_x, max_x) = (p1.x.min(p2.x), p1.x.max(p2.x));
+        let (min_y, max_y) = (p1.y.min(p2.y), p1.y.max(p2.y));
+        if min_y < self.min_y || max_y > self.max_y {
+            return false;
         }
 
-        0
+        let runs = cache
+            .entry((min_x, max_x))
+            .or_insert_with(|| self.span_runs(min_x, max_x));
+        runs[(min_y - self.min_y) as usize] > (max_y - self.min_y) as usize
+    }
+
+    /// `largest_valid_rectangle` using the span cache, for inputs where many candidates
+    /// share x-spans
+    #[allow(dead_code)]
+    fn largest_valid_rectangle_cached(&self) -> Option<(u64, usize, usize)> {
+        let mut cache = HashMap::new();
+        self.sorted_candidates().into_iter().find(|&(_, i, j)| {
+            self.is_valid_rectangle_cached(self.tiles[i], self.tiles[j], &mut cache)
+        })
+    }
+
+    /// Find the area of the largest valid rectangle at least `min_w` wide and `min_h` tall,
+    /// or 0 if none qualify
+    #[allow(dead_code)]
+    fn largest_valid_rectangle_min_dims(&self, min_w: u64, min_h: u64) -> u64 {
+        self.sorted_candidates()
+            .into_iter()
+            .filter(|&(_, i, j)| {
+                let (width, height) = self.dimensions(i, j);
+                width >= min_w && height >= min_h
+            })
+            .find(|&(_, i, j)| self.is_valid_rectangle(self.tiles[i], self.tiles[j]))
+            .map_or(0, |(area, _, _)| area)
+    }
+
+    /// Find the area of the largest valid rectangle, or 0 if none exists
+    fn largest_valid_rectangle_area(&self) -> u64 {
+        self.largest_valid_rectangle()
+            .map_or(0, |(area, _, _)| area)
     }
 }
 
+/// Solve both parts for each input, yielding (0, 0) for inputs that fail to parse
+#[allow(dead_code)]
+fn solve_many(inputs: &[&str]) -> Vec<(u64, u64)> {
+    inputs
+        .iter()
+        .map(|&input| {
+            TileGrid::try_from(input).map_or((0, 0), |grid| {
+                (
+                    grid.largest_rectangle_area(),
+                    grid.largest_valid_rectangle_area(),
+                )
+            })
+        })
+        .collect()
+}
+
 pub fn part_one(input: &str) -> Option<u64> {
     let grid = TileGrid::try_from(input).ok()?;
     Some(grid.largest_rectangle_area())
@@ -252,4 +429,162 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(24));
     }
+
+    #[test]
+    fn test_valid_span_per_row() {
+        let grid =
+            TileGrid::try_from(advent_of_code::template::read_file("examples", DAY).as_str())
+                .unwrap();
+        let spans = grid.valid_span_per_row();
+        assert_eq!(spans.first(), Some(&(1, 5)));
+        assert_eq!(spans.last(), Some(&(7, 3)));
+        // Polygon area including its boundary tiles
+        assert_eq!(spans.iter().map(|&(_, count)| count).sum::<u64>(), 46);
+    }
+
+    #[test]
+    fn test_solve_many() {
+        let example = advent_of_code::template::read_file("examples", DAY);
+        let square = "0,0\n4,0\n4,4\n0,4";
+        assert_eq!(
+            solve_many(&[&example, square, "not a tile"]),
+            vec![(50, 24), (25, 25), (0, 0)]
+        );
+    }
+
+    #[test]
+    fn test_equal_area_tie_break() {
+        // Both diagonals of the square span the same 5x5 area
+        let grid = TileGrid::try_from("0,0\n4,0\n4,4\n0,4").unwrap();
+        assert_eq!(grid.largest_valid_rectangle(), Some((25, 0, 2)));
+    }
+
+    #[test]
+    fn test_count_max_area_rectangles() {
+        // Both diagonals of a rectangle tie for its full area
+        let grid = TileGrid::try_from("0,0\n6,0\n6,2\n0,2").unwrap();
+        assert_eq!(grid.largest_rectangle_area(), 21);
+        assert_eq!(grid.count_max_area_rectangles(), 2);
+
+        // In a plus shape, four pairs of corners on neighbouring arms each span a 5x5 square,
+        // e.g. (2,0) and (6,4)
+        let plus = "2,0\n4,0\n4,2\n6,2\n6,4\n4,4\n4,6\n2,6\n2,4\n0,4\n0,2\n2,2";
+        let grid = TileGrid::try_from(plus).unwrap();
+        assert_eq!(grid.largest_rectangle_area(), 25);
+        assert_eq!(grid.count_max_area_rectangles(), 4);
+    }
+
+    #[test]
+    fn test_largest_red_corner_rectangle() {
+        // No four of the example's tiles form a rectangle's corners
+        let example = advent_of_code::template::read_file("examples", DAY);
+        let grid = TileGrid::try_from(example.as_str()).unwrap();
+        assert_eq!(grid.largest_rectangle_area(), 50);
+        assert_eq!(grid.largest_red_corner_rectangle(), 0);
+
+        // A U shape: the outer corners span 7x5, and the notch's corners 3x3
+        let grid = TileGrid::try_from("0,0\n6,0\n6,4\n4,4\n4,2\n2,2\n2,4\n0,4").unwrap();
+        assert_eq!(grid.largest_red_corner_rectangle(), 35);
+        // The notch's square on its own
+        let notch = TileGrid::try_from("2,2\n4,2\n4,4\n2,4").unwrap();
+        assert_eq!(notch.largest_red_corner_rectangle(), 9);
+        assert_eq!(notch.largest_rectangle_area(), 9);
+    }
+
+    #[test]
+    fn test_validate() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        assert!(TileGrid::validate(&input).is_ok());
+        assert!(TileGrid::validate("1,2\n3;4\n").is_err());
+        assert!(TileGrid::validate("1,2,3\n").is_err());
+        assert!(TileGrid::validate("").is_err());
+    }
+
+    #[test]
+    fn test_duplicate_tiles() {
+        let example = advent_of_code::template::read_file("examples", DAY);
+        let grid = TileGrid::try_from(example.as_str()).unwrap();
+        assert!(grid.duplicate_tiles().is_empty());
+        assert!(TileGrid::try_from_strict(&example).is_ok());
+
+        // Repeats sit next to their originals, so the boundary keeps axis-aligned edges
+        let duplicated = example.replacen("11,7\n", "11,7\n11,7\n11,7\n", 1) + "7,1\n";
+        let grid = TileGrid::try_from(duplicated.as_str()).unwrap();
+        assert_eq!(
+            grid.duplicate_tiles(),
+            vec![Point { x: 11, y: 7 }, Point { x: 7, y: 1 }]
+        );
+        assert!(TileGrid::try_from_strict(&duplicated).is_err());
+    }
+
+    #[test]
+    fn test_shuffled_square() {
+        let grid = TileGrid::try_from("0,0\n4,4\n4,0\n0,4").unwrap();
+        let corners = |coords: &[(i32, i32)]| -> Vec<Point> {
+            coords.iter().map(|&(x, y)| Point { x, y }).collect()
+        };
+        assert_eq!(
+            grid.ordered_boundary(),
+            corners(&[(0, 0), (4, 0), (4, 4), (0, 4)])
+        );
+        assert_eq!(grid.largest_valid_rectangle_area(), 25);
+
+        // Sorting by angle can't recover a non-convex outline, so a shuffled U shape is
+        // rejected, as is a square with a stray tile inside
+        assert!(TileGrid::try_from("0,0\n2,4\n6,0\n4,2\n6,4\n2,2\n4,4\n0,4").is_err());
+        assert!(TileGrid::try_from("0,0\n4,4\n4,0\n0,4\n1,2").is_err());
+
+        // Inputs that already form a polygon keep their order
+        let example =
+            TileGrid::try_from(advent_of_code::template::read_file("examples", DAY).as_str())
+                .unwrap();
+        assert_eq!(example.ordered_boundary(), example.tiles);
+    }
+
+    #[test]
+    fn test_cached_validity_matches_direct() {
+        let example = advent_of_code::template::read_file("examples", DAY);
+        for input in [example.as_str(), "0,0\n4,0\n4,4\n0,4"] {
+            let grid = TileGrid::try_from(input).unwrap();
+            let mut cache = HashMap::new();
+
+            for &p1 in &grid.tiles {
+                for &p2 in &grid.tiles {
+                    assert_eq!(
+                        grid.is_valid_rectangle_cached(p1, p2, &mut cache),
+                        grid.is_valid_rectangle(p1, p2),
+                        "{p1:?} {p2:?}"
+                    );
+                }
+            }
+            assert_eq!(
+                grid.largest_valid_rectangle_cached(),
+                grid.largest_valid_rectangle()
+            );
+        }
+    }
+
+    #[test]
+    fn test_largest_valid_rectangle_min_dims() {
+        let grid =
+            TileGrid::try_from(advent_of_code::template::read_file("examples", DAY).as_str())
+                .unwrap();
+        assert_eq!(grid.largest_valid_rectangle_min_dims(0, 0), 24);
+        // The 8x3 best rectangle is too short, leaving the 3x7 one on the right
+        assert_eq!(grid.largest_valid_rectangle_min_dims(1, 4), 21);
+        assert_eq!(grid.largest_valid_rectangle_min_dims(100, 1), 0);
+    }
+
+    #[test]
+    fn test_perimeter() {
+        let example = advent_of_code::template::read_file("examples", DAY);
+        assert_eq!(
+            TileGrid::try_from(example.as_str()).unwrap().perimeter(),
+            30
+        );
+
+        // Shuffled corners are walked in boundary order, not input order
+        let square = TileGrid::try_from("0,0\n4,4\n4,0\n0,4").unwrap();
+        assert_eq!(square.perimeter(), 16);
+    }
 }
@@ -1,16 +1,27 @@
 advent_of_code::solution!(10);
 
+use advent_of_code::linalg::{TooManyFreeVariables, all_min_weight_gf2, min_weight_gf2};
 use std::collections::HashSet;
 
 const EPSILON: f64 = 1e-10;
 const SOLUTION_TOLERANCE: f64 = 0.01;
 const MAX_SEARCH_ITERATIONS: usize = 10_000_000;
+const MAX_LIGHTS: usize = u128::BITS as usize;
+const MAX_LIGHT_SOLUTIONS: usize = 1024;
+// Above this many buttons, trying all 2^n press combinations gets too slow
+const BRUTE_FORCE_MAX_BUTTONS: usize = 20;
+
+/// A machine has too many buttons to enumerate every combination of presses as a u64 mask
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TooManyButtons;
 
 #[derive(Debug)]
 struct Machine {
-    target_lights: Vec<bool>,
     button_effects: Vec<Vec<usize>>,
     target_counters: Vec<usize>,
+    // Lights packed one bit per light, for XOR-based toggling
+    target_mask: u128,
+    button_masks: Vec<u128>,
 }
 
 impl Machine {
@@ -19,13 +30,79 @@ impl Machine {
         let button_effects = Self::parse_buttons(line)?;
         let target_counters = Self::parse_counters(line)?;
 
+        Self::new(target_lights, button_effects, target_counters)
+    }
+
+    /// Returns None if the machine has more lights than fit in a packed mask
+    fn new(
+        target_lights: Vec<bool>,
+        button_effects: Vec<Vec<usize>>,
+        target_counters: Vec<usize>,
+    ) -> Option<Self> {
+        if target_lights.len() > MAX_LIGHTS {
+            return None;
+        }
+
+        let target_mask = Self::pack_lights(
+            target_lights
+                .iter()
+                .enumerate()
+                .filter(|&(_, &on)| on)
+                .map(|(light_idx, _)| light_idx),
+        );
+        let button_masks = button_effects
+            .iter()
+            .map(|button| {
+                Self::pack_lights(
+                    button
+                        .iter()
+                        .copied()
+                        .filter(|&light_idx| light_idx < target_lights.len()),
+                )
+            })
+            .collect();
+
         Some(Machine {
-            target_lights,
             button_effects,
             target_counters,
+            target_mask,
+            button_masks,
         })
     }
 
+    /// The same machine with each set of buttons that affect the same lights and counters
+    /// collapsed into its first button.
+    ///
+    /// Identical buttons are interchangeable, so presses of the copies can all be moved onto
+    /// one of them: the minimal press counts for lights and counters are unchanged, while
+    /// the solvers have fewer buttons to search over.
+    fn dedup_equivalent_buttons(&self) -> Machine {
+        let mut seen = HashSet::new();
+        let (button_effects, button_masks) = self
+            .button_effects
+            .iter()
+            .zip(&self.button_masks)
+            .filter(|(effects, _)| {
+                let mut key = effects.to_vec();
+                key.sort_unstable();
+                key.dedup();
+                seen.insert(key)
+            })
+            .map(|(effects, &mask)| (effects.clone(), mask))
+            .unzip();
+
+        Machine {
+            button_effects,
+            target_counters: self.target_counters.clone(),
+            target_mask: self.target_mask,
+            button_masks,
+        }
+    }
+
+    fn pack_lights(light_indices: impl Iterator<Item = usize>) -> u128 {
+        light_indices.fold(0, |mask, light_idx| mask | (1 << light_idx))
+    }
+
     fn parse_target_lights(line: &str) -> Option<Vec<bool>> {
         let start = line.find('[')?;
         let end = line.find(']')?;
@@ -72,39 +149,220 @@ impl Machine {
         )
     }
 
-    // Part 1: Light toggle problem (XOR logic)
-    fn min_light_presses(&self) -> usize {
-        let num_buttons = self.button_effects.len();
-        let num_lights = self.target_lights.len();
-        let mut min_presses = usize::MAX;
-
-        // Try all 2^n combinations (each button pressed 0 or 1 times)
-        for mask in 0u32..(1 << num_buttons) {
-            let mut lights = vec![false; num_lights];
-            let presses = mask.count_ones() as usize;
-
-            for (button_idx, button_effects) in self.button_effects.iter().enumerate() {
-                if mask & (1 << button_idx) != 0 {
-                    for &light_idx in button_effects {
-                        if light_idx < num_lights {
-                            lights[light_idx] = !lights[light_idx];
-                        }
+    // Part 1: Light toggle problem (XOR logic).
+    // None if the target is unreachable, or if the buttons are too redundant for the GF(2)
+    // solver to search (see `MAX_FREE_VARIABLES_GF2`) even after dropping duplicates.
+    fn min_light_presses(&self) -> Option<usize> {
+        let press_set = if self.button_masks.len() <= BRUTE_FORCE_MAX_BUTTONS {
+            self.min_light_press_set().ok()?
+        } else {
+            match self.min_light_press_set_gf2() {
+                Ok(press_set) => press_set,
+                Err(_) => {
+                    // Duplicate buttons only add free variables, so retry without them
+                    let deduped = self.dedup_equivalent_buttons();
+                    if deduped.button_masks.len() == self.button_masks.len() {
+                        return None;
                     }
+                    return deduped.min_light_presses();
                 }
             }
+        };
+        press_set.map(|buttons| buttons.len())
+    }
+
+    /// Button indices of a minimal toggle solution, found as the minimum-weight
+    /// solution of the light system over GF(2), or None if the target is unreachable
+    fn min_light_press_set_gf2(&self) -> Result<Option<Vec<usize>>, TooManyFreeVariables> {
+        let (matrix, rhs) = self.light_system();
+        let presses = min_weight_gf2(&matrix, &rhs)?;
+        Ok(presses.map(|presses| self.pressed_buttons(&presses)))
+    }
+
+    /// Every minimal toggle solution as sorted button index lists, in lexicographic order.
+    /// Highly degenerate machines can have exponentially many, so at most
+    /// `MAX_LIGHT_SOLUTIONS` are returned.
+    #[allow(dead_code)]
+    fn all_min_light_press_sets(&self) -> Result<Vec<Vec<usize>>, TooManyFreeVariables> {
+        let (matrix, rhs) = self.light_system();
+        let mut sets: Vec<Vec<usize>> = all_min_weight_gf2(&matrix, &rhs, MAX_LIGHT_SOLUTIONS)?
+            .iter()
+            .map(|presses| self.pressed_buttons(presses))
+            .collect();
+        sets.sort_unstable();
+        Ok(sets)
+    }
+
+    /// The light system over GF(2): one equation per light, one variable per button
+    fn light_system(&self) -> (Vec<Vec<bool>>, Vec<bool>) {
+        // Only lights touched by the target or a button take part in the system
+        let used_lights = self
+            .button_masks
+            .iter()
+            .fold(self.target_mask, |used, &mask| used | mask);
+        let num_lights = (u128::BITS - used_lights.leading_zeros()) as usize;
+
+        let matrix: Vec<Vec<bool>> = (0..num_lights)
+            .map(|light_idx| {
+                self.button_masks
+                    .iter()
+                    .map(|&mask| mask & (1 << light_idx) != 0)
+                    .collect()
+            })
+            .collect();
+        let rhs: Vec<bool> = (0..num_lights)
+            .map(|light_idx| self.target_mask & (1 << light_idx) != 0)
+            .collect();
+
+        (matrix, rhs)
+    }
+
+    fn pressed_buttons(&self, presses: &[bool]) -> Vec<usize> {
+        (0..self.button_masks.len())
+            .filter(|&button_idx| presses.get(button_idx).copied().unwrap_or(false))
+            .collect()
+    }
+
+    /// Button indices of a minimal toggle solution, found by trying every button
+    /// combination, or None if the target is unreachable.
+    ///
+    /// Combinations are enumerated as a u64 bitmask, so machines with 64 or more buttons
+    /// are rejected; anything above `BRUTE_FORCE_MAX_BUTTONS` is already too slow.
+    fn min_light_press_set(&self) -> Result<Option<Vec<usize>>, TooManyButtons> {
+        let num_buttons = self.button_masks.len();
+        if num_buttons >= u64::BITS as usize {
+            return Err(TooManyButtons);
+        }
+        let mut best_mask: Option<u64> = (self.target_mask == 0).then_some(0);
+        let mut lights = 0u128;
 
-            if lights == self.target_lights {
-                min_presses = min_presses.min(presses);
+        // Walk all 2^n combinations in Gray code order, so each step toggles one button
+        for step in 1u64..(1 << num_buttons) {
+            lights ^= self.button_masks[step.trailing_zeros() as usize];
+            let mask = step ^ (step >> 1);
+
+            if lights == self.target_mask
+                && best_mask.is_none_or(|best| mask.count_ones() < best.count_ones())
+            {
+                best_mask = Some(mask);
             }
         }
 
-        min_presses
+        Ok(best_mask.map(|mask| {
+            (0..num_buttons)
+                .filter(|&button_idx| mask & (1 << button_idx) != 0)
+                .collect()
+        }))
     }
 
     // Part 2: Counter increment problem (integer linear programming)
     fn min_counter_presses(&self) -> Option<usize> {
         LinearSolver::new(self).solve()
     }
+
+    // Part 2 upper bound that prunes branches within `prune_slack` of the best cost,
+    // trading exactness for speed: a nonzero slack may return a non-minimal answer
+    #[allow(dead_code)]
+    fn min_counter_presses_with_slack(&self, prune_slack: usize) -> Option<usize> {
+        LinearSolver::new(self)
+            .with_prune_slack(prune_slack)
+            .solve()
+    }
+
+    // Part 2 variant where button i may be pressed at most caps[i] times (no limit if None).
+    // None if `caps` doesn't hold exactly one cap per button.
+    #[allow(dead_code)]
+    fn min_counter_presses_capped(&self, caps: Option<&[usize]>) -> Option<usize> {
+        if caps.is_some_and(|caps| caps.len() != self.button_effects.len()) {
+            return None;
+        }
+        LinearSolver::new(self).with_caps(caps).solve()
+    }
+
+    // Part 2 variant where pressing button i costs costs[i] instead of 1.
+    // None if `costs` doesn't hold exactly one cost per button.
+    #[allow(dead_code)]
+    fn min_weighted_counter_presses(&self, costs: &[usize]) -> Option<usize> {
+        if costs.len() != self.button_effects.len() {
+            return None;
+        }
+        LinearSolver::with_costs(self, costs.to_vec()).solve()
+    }
+
+    // Part 2 press counts per button for a minimal solution
+    #[allow(dead_code)]
+    fn min_counter_press_vector(&self) -> Option<Vec<usize>> {
+        LinearSolver::new(self).solve_presses()
+    }
+
+    /// Check that pressing each button `presses[i]` times hits every counter exactly.
+    /// Independent of `LinearSolver`, so it can be used to validate its output.
+    #[allow(dead_code)]
+    fn verify_counter_solution(&self, presses: &[usize]) -> bool {
+        if presses.len() != self.button_effects.len() {
+            return false;
+        }
+
+        let mut counters = vec![0; self.target_counters.len()];
+        for (button, &count) in self.button_effects.iter().zip(presses) {
+            for &counter_idx in button {
+                if let Some(counter) = counters.get_mut(counter_idx) {
+                    *counter += count;
+                }
+            }
+        }
+
+        counters == self.target_counters
+    }
+
+    // Upper bound for part 2: the most presses that still hit every counter exactly
+    #[allow(dead_code)]
+    fn max_counter_presses(&self) -> Option<usize> {
+        LinearSolver::new(self).solve_max()
+    }
+}
+
+/// Builds a `Machine` in code instead of parsing it from a line
+#[allow(dead_code)]
+#[derive(Debug, Default)]
+struct MachineBuilder {
+    target_lights: Vec<bool>,
+    button_effects: Vec<Vec<usize>>,
+    target_counters: Vec<usize>,
+}
+
+#[allow(dead_code)]
+impl MachineBuilder {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the light pattern to reach, true meaning on
+    fn target_lights(mut self, lights: &[bool]) -> Self {
+        self.target_lights = lights.to_vec();
+        self
+    }
+
+    /// Add a button that affects the given light/counter indices
+    fn button_effect(mut self, indices: &[usize]) -> Self {
+        self.button_effects.push(indices.to_vec());
+        self
+    }
+
+    /// Add the next counter's target value
+    fn target_counter(mut self, value: usize) -> Self {
+        self.target_counters.push(value);
+        self
+    }
+
+    /// Returns None under the same conditions as `Machine::new`
+    fn build(self) -> Option<Machine> {
+        Machine::new(
+            self.target_lights,
+            self.button_effects,
+            self.target_counters,
+        )
+    }
 }
 
 // Linear programming solver for Part 2
@@ -112,29 +370,170 @@ struct LinearSolver<'a> {
     machine: &'a Machine,
     num_buttons: usize,
     num_counters: usize,
+    costs: Vec<usize>,
+    prune_slack: usize,
+    // Most presses allowed per button, if limited
+    caps: Option<&'a [usize]>,
+    max_iterations: usize,
 }
 
 impl<'a> LinearSolver<'a> {
     fn new(machine: &'a Machine) -> Self {
+        Self::with_costs(machine, vec![1; machine.button_effects.len()])
+    }
+
+    /// Solver whose objective is `sum(presses[i] * costs[i])`
+    fn with_costs(machine: &'a Machine, costs: Vec<usize>) -> Self {
+        assert_eq!(
+            costs.len(),
+            machine.button_effects.len(),
+            "one cost per button"
+        );
         Self {
             machine,
             num_buttons: machine.button_effects.len(),
             num_counters: machine.target_counters.len(),
+            costs,
+            prune_slack: 0,
+            caps: None,
+            max_iterations: MAX_SEARCH_ITERATIONS,
         }
     }
 
+    /// Prune more aggressively during the free-variable search (see `OptimizationState`)
+    fn with_prune_slack(mut self, prune_slack: usize) -> Self {
+        self.prune_slack = prune_slack;
+        self
+    }
+
+    /// Limit button i to at most `caps[i]` presses; None leaves every button unbounded
+    fn with_caps(mut self, caps: Option<&'a [usize]>) -> Self {
+        if let Some(caps) = caps {
+            assert_eq!(caps.len(), self.num_buttons, "one cap per button");
+        }
+        self.caps = caps;
+        self
+    }
+
+    /// Give up the free-variable search after this many steps
+    #[allow(dead_code)]
+    fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Most presses `caps` allows for a button
+    fn cap(&self, button_idx: usize) -> usize {
+        self.caps.map_or(usize::MAX, |caps| caps[button_idx])
+    }
+
     fn solve(&self) -> Option<usize> {
+        self.solve_presses().map(|presses| self.cost(&presses))
+    }
+
+    /// Like `solve`, but also reports whether the search ran to completion. When it hit the
+    /// iteration cap the cost is only the best found so far, which may be non-minimal or None.
+    #[allow(dead_code)]
+    fn solve_best_effort(&self) -> (Option<usize>, bool) {
+        let (presses, complete) = self.search_presses();
+        (presses.map(|presses| self.cost(&presses)), complete)
+    }
+
+    /// Press counts per button of a minimum-cost solution
+    fn solve_presses(&self) -> Option<Vec<usize>> {
+        self.search_presses().0
+    }
+
+    /// Best press counts found, and whether the search finished within the iteration cap
+    fn search_presses(&self) -> (Option<Vec<usize>>, bool) {
         let matrix = self.build_augmented_matrix();
         let (reduced_matrix, pivot_cols) = self.gaussian_elimination(matrix);
         let free_vars = self.identify_free_variables(&pivot_cols);
 
         if free_vars.is_empty() {
-            return self.extract_solution(&reduced_matrix, &pivot_cols, &[], &[]);
+            let presses = self.extract_presses(&reduced_matrix, &pivot_cols, &[], &[]);
+            return (presses, true);
         }
 
         self.optimize_free_variables(&reduced_matrix, &pivot_cols, &free_vars)
     }
 
+    /// Maximize total presses instead of minimizing them.
+    /// Returns None if a button is unbounded, no solution exists, or the search is cut short.
+    fn solve_max(&self) -> Option<usize> {
+        match self.solve_max_best_effort() {
+            (best, true) => best,
+            (_, false) => None,
+        }
+    }
+
+    /// Like `solve_max`, but also reports whether the search ran to completion. A complete
+    /// search returns None only when a button is unbounded or no solution exists; when it
+    /// hit the iteration cap the total is only the best found so far, which may be None.
+    fn solve_max_best_effort(&self) -> (Option<usize>, bool) {
+        let Some(press_limits) = (0..self.num_buttons)
+            .map(|button_idx| self.press_limit(button_idx))
+            .collect::<Option<Vec<usize>>>()
+        else {
+            return (None, true);
+        };
+
+        let matrix = self.build_augmented_matrix();
+        let (reduced_matrix, pivot_cols) = self.gaussian_elimination(matrix);
+        let free_vars = self.identify_free_variables(&pivot_cols);
+        let bounds: Vec<usize> = free_vars.iter().map(|&col| press_limits[col]).collect();
+
+        let mut state = MaxSearchState::new(self.max_iterations);
+        self.search_max(
+            &reduced_matrix,
+            &pivot_cols,
+            &free_vars,
+            &bounds,
+            &mut Vec::new(),
+            &mut state,
+        );
+
+        (state.best, !state.budget.cut_short())
+    }
+
+    /// Most times a button can be pressed without overshooting any counter it affects.
+    /// A button touching no counter is unbounded.
+    fn press_limit(&self, button_idx: usize) -> Option<usize> {
+        self.machine.button_effects[button_idx]
+            .iter()
+            .filter_map(|&counter_idx| self.machine.target_counters.get(counter_idx))
+            .min()
+            .copied()
+    }
+
+    fn search_max(
+        &self,
+        matrix: &[Vec<f64>],
+        pivot_cols: &[usize],
+        free_vars: &[usize],
+        bounds: &[usize],
+        current_values: &mut Vec<usize>,
+        state: &mut MaxSearchState,
+    ) {
+        if state.budget.should_terminate() {
+            return;
+        }
+
+        if current_values.len() == free_vars.len() {
+            if let Some(cost) = self.extract_solution(matrix, pivot_cols, free_vars, current_values)
+            {
+                state.update_best(cost);
+            }
+            return;
+        }
+
+        for val in 0..=bounds[current_values.len()] {
+            current_values.push(val);
+            self.search_max(matrix, pivot_cols, free_vars, bounds, current_values, state);
+            current_values.pop();
+        }
+    }
+
     fn build_augmented_matrix(&self) -> Vec<Vec<f64>> {
         let mut matrix = vec![vec![0.0; self.num_buttons + 1]; self.num_counters];
 
@@ -174,16 +573,18 @@ impl<'a> LinearSolver<'a> {
 
             // Normalize pivot row
             let pivot = matrix[current_row][col];
-            for j in col..=self.num_buttons {
-                matrix[current_row][j] /= pivot;
+            for value in &mut matrix[current_row][col..] {
+                *value /= pivot;
             }
 
             // Eliminate column in all other rows
-            for row in 0..self.num_counters {
-                if row != current_row && matrix[row][col].abs() > EPSILON {
-                    let factor = matrix[row][col];
-                    for j in col..=self.num_buttons {
-                        matrix[row][j] -= factor * matrix[current_row][j];
+            let pivot_values = matrix[current_row].clone();
+            for (row, values) in matrix.iter_mut().enumerate() {
+                if row != current_row && values[col].abs() > EPSILON {
+                    let factor = values[col];
+                    for (value, &pivot_value) in values[col..].iter_mut().zip(&pivot_values[col..])
+                    {
+                        *value -= factor * pivot_value;
                     }
                 }
             }
@@ -209,9 +610,9 @@ impl<'a> LinearSolver<'a> {
         matrix: &[Vec<f64>],
         pivot_cols: &[usize],
         free_vars: &[usize],
-    ) -> Option<usize> {
+    ) -> (Option<Vec<usize>>, bool) {
         let bounds = self.compute_free_variable_bounds(matrix, free_vars);
-        let mut search_state = OptimizationState::new();
+        let mut search_state = OptimizationState::new(self.prune_slack, self.max_iterations);
 
         self.search_free_variables(
             matrix,
@@ -222,7 +623,8 @@ impl<'a> LinearSolver<'a> {
             &mut search_state,
         );
 
-        search_state.best_cost()
+        let complete = !search_state.budget.cut_short();
+        (search_state.best_presses, complete)
     }
 
     fn compute_free_variable_bounds(&self, matrix: &[Vec<f64>], free_vars: &[usize]) -> Vec<usize> {
@@ -244,7 +646,7 @@ impl<'a> LinearSolver<'a> {
                     .max()
                     .unwrap_or(0);
 
-                constraint_bound.min(max_target)
+                constraint_bound.min(max_target).min(self.cap(free_col))
             })
             .collect()
     }
@@ -258,26 +660,32 @@ impl<'a> LinearSolver<'a> {
         current_values: &mut Vec<usize>,
         state: &mut OptimizationState,
     ) {
-        if state.should_terminate() {
+        if state.budget.should_terminate() {
             return;
         }
 
         if current_values.len() == free_vars.len() {
-            if let Some(cost) = self.extract_solution(matrix, pivot_cols, free_vars, current_values)
+            if let Some(presses) =
+                self.extract_presses(matrix, pivot_cols, free_vars, current_values)
             {
-                state.update_best(cost);
+                state.update_best(self.cost(&presses), presses);
             }
             return;
         }
 
         let depth = current_values.len();
-        let current_sum: usize = current_values.iter().sum();
+        let current_cost: usize = current_values
+            .iter()
+            .zip(free_vars)
+            .map(|(&val, &free_col)| val * self.costs[free_col])
+            .sum();
 
-        if state.should_prune(current_sum) {
+        if state.should_prune(current_cost) {
             return;
         }
 
-        let max_val = bounds[depth].min(state.remaining_budget(current_sum));
+        let unit_cost = self.costs[free_vars[depth]];
+        let max_val = bounds[depth].min(state.remaining_budget(current_cost, unit_cost));
 
         for val in 0..=max_val {
             current_values.push(val);
@@ -293,7 +701,7 @@ impl<'a> LinearSolver<'a> {
 
             current_values.pop();
 
-            if state.can_terminate_early(current_sum + val) {
+            if state.can_terminate_early(current_cost + val * unit_cost) {
                 break;
             }
         }
@@ -306,6 +714,19 @@ impl<'a> LinearSolver<'a> {
         free_vars: &[usize],
         free_values: &[usize],
     ) -> Option<usize> {
+        self.extract_presses(matrix, pivot_cols, free_vars, free_values)
+            .map(|presses| self.cost(&presses))
+    }
+
+    /// Back-substitute free variable values into press counts for every button,
+    /// or None if that doesn't give a non-negative integer solution
+    fn extract_presses(
+        &self,
+        matrix: &[Vec<f64>],
+        pivot_cols: &[usize],
+        free_vars: &[usize],
+        free_values: &[usize],
+    ) -> Option<Vec<usize>> {
         let mut solution = vec![0.0; self.num_buttons];
 
         // Set free variable values
@@ -329,13 +750,24 @@ impl<'a> LinearSolver<'a> {
             return None;
         }
 
-        Some(solution.iter().map(|&v| v.round() as usize).sum())
+        Some(solution.iter().map(|&v| v.round() as usize).collect())
+    }
+
+    /// Objective value of a press vector: `sum(presses[i] * costs[i])`
+    fn cost(&self, presses: &[usize]) -> usize {
+        presses
+            .iter()
+            .zip(&self.costs)
+            .map(|(&count, &cost)| count * cost)
+            .sum()
     }
 
     fn is_valid_solution(&self, solution: &[f64]) -> bool {
-        // Check all values are non-negative integers
-        if !solution.iter().all(|&val| {
-            val >= -SOLUTION_TOLERANCE && (val - val.round()).abs() <= SOLUTION_TOLERANCE
+        // Check all values are non-negative integers within their caps
+        if !solution.iter().enumerate().all(|(button_idx, &val)| {
+            val >= -SOLUTION_TOLERANCE
+                && (val - val.round()).abs() <= SOLUTION_TOLERANCE
+                && val.round() <= self.cap(button_idx) as f64
         }) {
             return false;
         }
@@ -360,70 +792,128 @@ impl<'a> LinearSolver<'a> {
     }
 }
 
+/// Counts search steps against the solver's iteration cap
+struct IterationBudget {
+    iterations: usize,
+    max_iterations: usize,
+}
+
+impl IterationBudget {
+    fn new(max_iterations: usize) -> Self {
+        Self {
+            iterations: 0,
+            max_iterations,
+        }
+    }
+
+    /// Take one step, returning true once the cap has been passed
+    fn should_terminate(&mut self) -> bool {
+        self.iterations += 1;
+        self.cut_short()
+    }
+
+    /// Whether the search ran past its iteration cap
+    fn cut_short(&self) -> bool {
+        self.iterations > self.max_iterations
+    }
+}
+
 // Optimization state tracking
 struct OptimizationState {
     best_cost: usize,
-    iterations: usize,
+    best_presses: Option<Vec<usize>>,
+    budget: IterationBudget,
+    // Branches within this much of the best cost are pruned too; 0 keeps the search exact,
+    // anything larger may return a non-minimal answer
+    prune_slack: usize,
 }
 
 impl OptimizationState {
-    fn new() -> Self {
+    fn new(prune_slack: usize, max_iterations: usize) -> Self {
         Self {
             best_cost: usize::MAX,
-            iterations: 0,
+            best_presses: None,
+            budget: IterationBudget::new(max_iterations),
+            prune_slack,
         }
     }
 
-    fn update_best(&mut self, cost: usize) {
-        self.best_cost = self.best_cost.min(cost);
+    fn update_best(&mut self, cost: usize, presses: Vec<usize>) {
+        if cost < self.best_cost {
+            self.best_cost = cost;
+            self.best_presses = Some(presses);
+        }
     }
 
-    fn should_terminate(&mut self) -> bool {
-        self.iterations += 1;
-        self.iterations > MAX_SEARCH_ITERATIONS
+    fn should_prune(&self, current_cost: usize) -> bool {
+        current_cost.saturating_add(self.prune_slack) >= self.best_cost
     }
 
-    fn should_prune(&self, current_sum: usize) -> bool {
-        current_sum >= self.best_cost
+    /// How many more presses of a button costing `unit_cost` fit under the best cost
+    fn remaining_budget(&self, current_cost: usize, unit_cost: usize) -> usize {
+        let budget = self.best_cost.saturating_sub(current_cost);
+        if unit_cost == 0 || budget == usize::MAX {
+            budget
+        } else {
+            budget / unit_cost
+        }
     }
 
-    fn remaining_budget(&self, current_sum: usize) -> usize {
-        self.best_cost.saturating_sub(current_sum)
+    fn can_terminate_early(&self, current_cost: usize) -> bool {
+        self.best_cost < usize::MAX && self.best_cost <= current_cost
     }
+}
 
-    fn can_terminate_early(&self, current_sum: usize) -> bool {
-        self.best_cost < usize::MAX && self.best_cost <= current_sum
-    }
+/// State of the press-maximizing search
+struct MaxSearchState {
+    best: Option<usize>,
+    budget: IterationBudget,
+}
 
-    fn best_cost(&self) -> Option<usize> {
-        if self.best_cost == usize::MAX {
-            None
-        } else {
-            Some(self.best_cost)
+impl MaxSearchState {
+    fn new(max_iterations: usize) -> Self {
+        Self {
+            best: None,
+            budget: IterationBudget::new(max_iterations),
         }
     }
+
+    fn update_best(&mut self, total: usize) {
+        self.best = Some(self.best.map_or(total, |best| best.max(total)));
+    }
 }
 
+/// Sum the minimum light presses over all machines. None if any machine can't reach its
+/// target, or is too redundant to search (see `Machine::min_light_presses`), since the
+/// total would be meaningless without it.
 pub fn part_one(input: &str) -> Option<usize> {
-    let total: usize = input
+    input
         .lines()
         .filter(|line| !line.is_empty())
         .filter_map(Machine::parse)
         .map(|machine| machine.min_light_presses())
-        .sum();
+        .sum()
+}
 
-    Some(total)
+/// Sum the minimum counter presses over all machines, along with the indices of the
+/// machines that were skipped (unparseable, infeasible or search-limit exceeded)
+fn part_two_report(input: &str) -> (u64, Vec<usize>) {
+    let mut total = 0;
+    let mut skipped = Vec::new();
+
+    for (idx, line) in input.lines().filter(|line| !line.is_empty()).enumerate() {
+        match Machine::parse(line).and_then(|machine| machine.min_counter_presses()) {
+            Some(presses) => total += presses as u64,
+            None => skipped.push(idx),
+        }
+    }
+
+    (total, skipped)
 }
 
 pub fn part_two(input: &str) -> Option<usize> {
-    let total: usize = input
-        .lines()
-        .filter(|line| !line.is_empty())
-        .filter_map(Machine::parse)
-        .filter_map(|machine| machine.min_counter_presses())
-        .sum();
-
-    Some(total)
+    let (total, _) = part_two_report(input);
+    Some(total as usize)
 }
 
 #[cfg(test)]
@@ -441,4 +931,303 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(33));
     }
+
+    #[test]
+    fn test_part_two_report() {
+        let input = "[.#] (0) (1) {1,2}\n[..] (0) {1,1}\n[..] (0) (1) (0,1) {2,2}\n";
+        assert_eq!(part_two_report(input), (5, vec![1]));
+
+        let example = advent_of_code::template::read_file("examples", DAY);
+        assert_eq!(part_two_report(&example), (33, vec![]));
+    }
+
+    #[test]
+    fn test_machine_builder() {
+        // Same as the example's first machine: [.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}
+        let machine = MachineBuilder::new()
+            .target_lights(&[false, true, true, false])
+            .button_effect(&[3])
+            .button_effect(&[1, 3])
+            .button_effect(&[2])
+            .button_effect(&[2, 3])
+            .button_effect(&[0, 2])
+            .button_effect(&[0, 1])
+            .target_counter(3)
+            .target_counter(5)
+            .target_counter(4)
+            .target_counter(7)
+            .build()
+            .unwrap();
+
+        assert_eq!(machine.min_light_presses(), Some(2));
+        assert_eq!(machine.min_counter_presses(), Some(10));
+    }
+
+    #[test]
+    fn test_prune_slack() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        for machine in input.lines().filter_map(Machine::parse) {
+            assert_eq!(
+                machine.min_counter_presses_with_slack(0),
+                machine.min_counter_presses()
+            );
+        }
+
+        // Three presses of (0,1) are optimal, but slack prunes that branch early
+        let machine = Machine::parse("[..] (0) (1) (0,1) (1) {3,3}").unwrap();
+        assert_eq!(machine.min_counter_presses(), Some(3));
+        assert_eq!(machine.min_counter_presses_with_slack(1), Some(4));
+        assert_eq!(machine.min_counter_presses_with_slack(3), Some(5));
+    }
+
+    #[test]
+    fn test_capped_counter_presses() {
+        // Two presses of (0,1) are optimal; capping it at one leaves (0) and (1) to make up
+        // the rest, and capping those at zero as well leaves no solution
+        let machine = Machine::parse("[..] (0) (1) (0,1) {2,2}").unwrap();
+        assert_eq!(machine.min_counter_presses_capped(None), Some(2));
+        assert_eq!(
+            machine.min_counter_presses_capped(Some(&[1, 1, 1])),
+            Some(3)
+        );
+        assert_eq!(machine.min_counter_presses_capped(Some(&[0, 0, 1])), None);
+        assert_eq!(
+            machine.min_counter_presses_capped(Some(&[0, 0, 2])),
+            Some(2)
+        );
+
+        assert_eq!(machine.min_counter_presses_capped(Some(&[2, 2])), None);
+        assert_eq!(
+            machine.min_counter_presses_capped(Some(&[2, 2, 2, 2])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_weighted_counter_presses() {
+        // Two presses of (0,1) beat (0)+(1) twice over unless (0,1) is expensive
+        let machine = Machine::parse("[..] (0) (1) (0,1) {2,2}").unwrap();
+        assert_eq!(machine.min_weighted_counter_presses(&[1, 1, 1]), Some(2));
+        assert_eq!(machine.min_weighted_counter_presses(&[1, 1, 5]), Some(4));
+        assert_eq!(machine.min_weighted_counter_presses(&[3, 1, 5]), Some(8));
+
+        assert_eq!(machine.min_weighted_counter_presses(&[1, 1]), None);
+        assert_eq!(machine.min_weighted_counter_presses(&[1, 1, 1, 1]), None);
+    }
+
+    #[test]
+    fn test_min_light_press_set() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let mut total = 0;
+
+        for machine in input.lines().filter_map(Machine::parse) {
+            let buttons = machine.min_light_press_set().unwrap().unwrap();
+            let mut lights = 0u128;
+            for &button_idx in &buttons {
+                for &light_idx in &machine.button_effects[button_idx] {
+                    lights ^= 1 << light_idx;
+                }
+            }
+            assert_eq!(lights, machine.target_mask);
+            total += buttons.len();
+        }
+
+        assert_eq!(total, 7);
+    }
+
+    #[test]
+    fn test_gf2_matches_brute_force() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        for machine in input.lines().filter_map(Machine::parse) {
+            let gf2 = machine.min_light_press_set_gf2().unwrap().unwrap();
+            let brute_force = machine.min_light_press_set().unwrap().unwrap();
+            assert_eq!(gf2.len(), brute_force.len());
+        }
+
+        let unreachable = Machine::parse("[#.] (1) {0,0}").unwrap();
+        assert_eq!(unreachable.min_light_press_set_gf2(), Ok(None));
+        assert_eq!(unreachable.min_light_presses(), None);
+    }
+
+    #[test]
+    fn test_all_min_light_press_sets() {
+        // (0)+(1,2) and (0,1)+(2) both light everything in two presses
+        let machine = Machine::parse("[###] (0) (1,2) (0,1) (2) {0,0,0}").unwrap();
+        assert_eq!(
+            machine.all_min_light_press_sets(),
+            Ok(vec![vec![0, 1], vec![2, 3]])
+        );
+        assert_eq!(machine.min_light_presses(), Some(2));
+
+        let unreachable = Machine::parse("[#.] (1) {0,0}").unwrap();
+        assert_eq!(unreachable.all_min_light_press_sets(), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_light_strategy_switch() {
+        // Few enough buttons for brute force
+        let small = Machine::parse("[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}").unwrap();
+        assert_eq!(small.min_light_presses(), Some(2));
+        assert_eq!(small.min_light_press_set_gf2().unwrap().unwrap().len(), 2);
+
+        // 21 single-light buttons and one that toggles lights 0-4 go to the GF(2) solver
+        let buttons: String = (0..21).map(|i| format!("({i}) ")).collect();
+        let line = format!("[#####{}#] {buttons}(0,1,2,3,4) {{}}", ".".repeat(15));
+        let large = Machine::parse(&line).unwrap();
+        assert!(large.button_masks.len() > BRUTE_FORCE_MAX_BUTTONS);
+        assert_eq!(large.min_light_presses(), Some(2));
+        assert_eq!(large.min_light_press_set().unwrap().unwrap().len(), 2);
+
+        // 70 copies of one button leave 69 free variables, too many to enumerate, but
+        // dropping the copies leaves a single button
+        let buttons = "(0) ".repeat(70);
+        let redundant = Machine::parse(&format!("[#] {buttons}{{}}")).unwrap();
+        assert_eq!(
+            redundant.min_light_press_set_gf2(),
+            Err(TooManyFreeVariables { free_vars: 69 })
+        );
+        assert_eq!(redundant.min_light_presses(), Some(1));
+
+        // Every distinct button over six lights leaves 57 free variables, with no
+        // duplicates to drop
+        let buttons: String = (1..64u32)
+            .map(|subset| {
+                let lights: Vec<String> = (0..6)
+                    .filter(|light| subset & (1 << light) != 0)
+                    .map(|light| light.to_string())
+                    .collect();
+                format!("({}) ", lights.join(","))
+            })
+            .collect();
+        let line = format!("[#.....] {buttons}{{}}");
+        let distinct = Machine::parse(&line).unwrap();
+        assert_eq!(
+            distinct.min_light_press_set_gf2(),
+            Err(TooManyFreeVariables { free_vars: 57 })
+        );
+        assert_eq!(distinct.min_light_presses(), None);
+        assert_eq!(part_one(&format!("[#] (0) {{}}\n{line}\n")), None);
+
+        // Brute force can't even count through 2^70 combinations
+        assert_eq!(redundant.min_light_press_set(), Err(TooManyButtons));
+    }
+
+    #[test]
+    fn test_dedup_equivalent_buttons() {
+        // The example's first machine with (1,3) and (0,2) listed twice, once reordered
+        let machine =
+            Machine::parse("[.##.] (3) (1,3) (2) (3,1) (2,3) (0,2) (0,1) (0,2) {3,5,4,7}").unwrap();
+        let deduped = machine.dedup_equivalent_buttons();
+        assert_eq!(
+            deduped.button_effects,
+            vec![
+                vec![3],
+                vec![1, 3],
+                vec![2],
+                vec![2, 3],
+                vec![0, 2],
+                vec![0, 1]
+            ]
+        );
+        assert_eq!(deduped.button_masks.len(), 6);
+
+        assert_eq!(deduped.min_light_presses(), machine.min_light_presses());
+        assert_eq!(deduped.min_counter_presses(), machine.min_counter_presses());
+        assert_eq!(deduped.min_counter_presses(), Some(10));
+    }
+
+    #[test]
+    fn test_many_lights() {
+        let lights: String = (0..128)
+            .map(|i| if i < 64 || i == 127 { '#' } else { '.' })
+            .collect();
+        let range = |r: std::ops::RangeInclusive<usize>| {
+            r.map(|i| i.to_string()).collect::<Vec<_>>().join(",")
+        };
+        let line = format!(
+            "[{lights}] ({}) (127) ({}) ({}) {{}}",
+            range(0..=63),
+            range(64..=126),
+            range(0..=127)
+        );
+
+        let machine = Machine::parse(&line).unwrap();
+        assert_eq!(machine.min_light_presses(), Some(2));
+
+        let too_many = format!("[{}] (0) {{}}", ".".repeat(129));
+        assert!(Machine::parse(&too_many).is_none());
+    }
+
+    #[test]
+    fn test_counter_press_vector_verifies() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        for machine in input.lines().filter_map(Machine::parse) {
+            let presses = machine.min_counter_press_vector().unwrap();
+            assert!(machine.verify_counter_solution(&presses));
+            assert_eq!(
+                presses.iter().sum::<usize>(),
+                machine.min_counter_presses().unwrap()
+            );
+        }
+
+        let machine = Machine::parse("[..] (0) (1) (0,1) {2,2}").unwrap();
+        assert!(machine.verify_counter_solution(&[1, 1, 1]));
+        assert!(!machine.verify_counter_solution(&[1, 0, 1]));
+        assert!(!machine.verify_counter_solution(&[2, 2]));
+    }
+
+    #[test]
+    fn test_max_counter_presses() {
+        // Pressing (0,1) k times leaves 2-k presses each for (0) and (1)
+        let machine = Machine::parse("[..] (0) (1) (0,1) {2,2}").unwrap();
+        assert_eq!(machine.min_counter_presses(), Some(2));
+        assert_eq!(machine.max_counter_presses(), Some(4));
+
+        // Cutting the search short is reported separately from having no solution
+        let solver = LinearSolver::new(&machine);
+        assert_eq!(solver.solve_max_best_effort(), (Some(4), true));
+        let (_, complete) = solver.with_max_iterations(1).solve_max_best_effort();
+        assert!(!complete);
+        assert_eq!(
+            LinearSolver::new(&machine)
+                .with_max_iterations(1)
+                .solve_max(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_max_counter_presses_unbounded() {
+        // Button (3) touches no counter, so it can be pressed any number of times
+        let machine = Machine::parse("[..] (0) (3) {1,0}").unwrap();
+        assert_eq!(machine.max_counter_presses(), None);
+        assert_eq!(
+            LinearSolver::new(&machine).solve_max_best_effort(),
+            (None, true)
+        );
+    }
+
+    #[test]
+    fn test_solve_best_effort() {
+        let machine = Machine::parse("[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}").unwrap();
+        assert_eq!(
+            LinearSolver::new(&machine).solve_best_effort(),
+            (Some(10), true)
+        );
+
+        let (cost, complete) = LinearSolver::new(&machine)
+            .with_max_iterations(1)
+            .solve_best_effort();
+        assert!(!complete);
+        assert_eq!(cost, None);
+
+        // No free variables means no search, so even a zero cap completes
+        let machine = Machine::parse("[..] (0) (1) {2,3}").unwrap();
+        assert_eq!(
+            LinearSolver::new(&machine)
+                .with_max_iterations(0)
+                .solve_best_effort(),
+            (Some(5), true)
+        );
+    }
 }
@@ -1,24 +1,84 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 advent_of_code::solution!(11);
 
 type Graph<'a> = HashMap<&'a str, Vec<&'a str>>;
 
-const DAC_BIT: u8 = 1;
-const FFT_BIT: u8 = 2;
-const BOTH_REQUIRED: u8 = DAC_BIT | FFT_BIT;
+/// Nodes a path must pass through, each tracked by one bit of a visit mask
+struct RequiredSet<'a> {
+    nodes: Vec<&'a str>,
+    bits: HashMap<&'a str, u32>,
+}
+
+impl<'a> RequiredSet<'a> {
+    fn new(nodes: Vec<&'a str>) -> Self {
+        assert!(nodes.len() <= 32, "at most 32 required nodes are supported");
+        let bits = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, &node)| (node, 1 << i))
+            .collect();
+        RequiredSet { nodes, bits }
+    }
+
+    /// The bit marking `node` as visited, or 0 if it isn't required
+    #[inline]
+    fn bit(&self, node: &str) -> u32 {
+        self.bits.get(node).copied().unwrap_or(0)
+    }
+
+    /// The mask of a path that visited every required node
+    fn full_mask(&self) -> u32 {
+        u32::MAX
+            .checked_shr(32 - self.nodes.len() as u32)
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug)]
+struct ParseError;
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Failed to parse graph: edge line without ':' separator")
+    }
+}
 
-fn parse_graph(input: &str) -> Graph<'_> {
+impl std::error::Error for ParseError {}
+
+/// Parse `node: neighbor ...` lines, skipping blank lines and `#` comments
+fn try_parse_graph(input: &str) -> Result<Graph<'_>, ParseError> {
     input
         .lines()
-        .filter_map(|line| {
-            let (node, neighbors) = line.split_once(':')?;
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (node, neighbors) = line.split_once(':').ok_or(ParseError)?;
             let neighbors = neighbors.split_whitespace().collect();
-            Some((node.trim(), neighbors))
+            Ok((node.trim(), neighbors))
         })
         .collect()
 }
 
+/// All nodes reachable from `start`, including `start` itself
+///
+/// Uses a visited set, so it terminates on cyclic graphs too.
+#[allow(dead_code)]
+fn reachable_from<'a>(graph: &Graph<'a>, start: &'a str) -> HashSet<&'a str> {
+    let mut visited = HashSet::from([start]);
+    let mut stack = vec![start];
+
+    while let Some(node) = stack.pop() {
+        for &neighbor in graph.get(node).into_iter().flatten() {
+            if visited.insert(neighbor) {
+                stack.push(neighbor);
+            }
+        }
+    }
+
+    visited
+}
+
 fn count_paths<'a>(
     graph: &Graph<'a>,
     current: &'a str,
@@ -47,15 +107,163 @@ fn count_paths<'a>(
     result
 }
 
+/// Count paths from `start` to each of several targets in a single memoized traversal
+#[allow(dead_code)]
+fn count_paths_multi<'a>(
+    graph: &Graph<'a>,
+    start: &'a str,
+    targets: &[&str],
+) -> HashMap<String, u64> {
+    let mut memo = HashMap::new();
+    let counts = count_paths_multi_recursive(graph, start, targets, &mut memo);
+
+    targets
+        .iter()
+        .zip(counts)
+        .map(|(&target, count)| (target.to_string(), count))
+        .collect()
+}
+
+/// Per-target path counts from `current`, indexed like `targets`
+fn count_paths_multi_recursive<'a>(
+    graph: &Graph<'a>,
+    current: &'a str,
+    targets: &[&str],
+    memo: &mut HashMap<&'a str, Vec<u64>>,
+) -> Vec<u64> {
+    if let Some(cached) = memo.get(current) {
+        return cached.clone();
+    }
+
+    let mut counts = vec![0; targets.len()];
+    for (count, &target) in counts.iter_mut().zip(targets) {
+        if current == target {
+            *count += 1;
+        }
+    }
+
+    for &neighbor in graph.get(current).into_iter().flatten() {
+        let neighbor_counts = count_paths_multi_recursive(graph, neighbor, targets, memo);
+        for (count, neighbor_count) in counts.iter_mut().zip(neighbor_counts) {
+            *count += neighbor_count;
+        }
+    }
+
+    memo.insert(current, counts.clone());
+    counts
+}
+
+/// Count paths from `start` to `target` that pass through `waypoint`
+///
+/// On a DAG a path visits the waypoint at most once, so this is the number of ways to
+/// reach it times the number of ways on from it (0 if it's unreachable).
+#[allow(dead_code)]
+fn count_paths_through<'a>(
+    graph: &Graph<'a>,
+    start: &'a str,
+    target: &str,
+    waypoint: &'a str,
+) -> u64 {
+    let to_waypoint = count_paths(graph, start, waypoint, &mut HashMap::new());
+    if to_waypoint == 0 {
+        return 0;
+    }
+    to_waypoint * count_paths(graph, waypoint, target, &mut HashMap::new())
+}
+
+/// Count paths from `start` to `target` that never use an edge listed in `forbidden`
+///
+/// Forbidden edges change which nodes can reach the target, so each call uses a fresh memo.
+#[allow(dead_code)]
+fn count_paths_excluding<'a>(
+    graph: &Graph<'a>,
+    start: &'a str,
+    target: &str,
+    forbidden: &HashSet<(&str, &str)>,
+) -> u64 {
+    fn count<'a>(
+        graph: &Graph<'a>,
+        current: &'a str,
+        target: &str,
+        forbidden: &HashSet<(&str, &str)>,
+        memo: &mut HashMap<&'a str, u64>,
+    ) -> u64 {
+        if current == target {
+            return 1;
+        }
+
+        if let Some(&cached) = memo.get(current) {
+            return cached;
+        }
+
+        let result = graph
+            .get(current)
+            .into_iter()
+            .flatten()
+            .filter(|&&neighbor| !forbidden.contains(&(current, neighbor)))
+            .map(|&neighbor| count(graph, neighbor, target, forbidden, memo))
+            .sum();
+
+        memo.insert(current, result);
+        result
+    }
+
+    count(graph, start, target, forbidden, &mut HashMap::new())
+}
+
+/// Count paths from `start` to `target` with at most `max_hops` edges
+///
+/// The hop budget bounds every path, so this terminates on cyclic graphs too.
+#[allow(dead_code)]
+fn count_paths_max_hops<'a>(
+    graph: &Graph<'a>,
+    start: &'a str,
+    target: &str,
+    max_hops: usize,
+) -> u64 {
+    fn count<'a>(
+        graph: &Graph<'a>,
+        current: &'a str,
+        target: &str,
+        remaining_hops: usize,
+        memo: &mut HashMap<(&'a str, usize), u64>,
+    ) -> u64 {
+        if current == target {
+            return 1;
+        }
+        if remaining_hops == 0 {
+            return 0;
+        }
+
+        let key = (current, remaining_hops);
+        if let Some(&cached) = memo.get(&key) {
+            return cached;
+        }
+
+        let result = graph
+            .get(current)
+            .into_iter()
+            .flatten()
+            .map(|&neighbor| count(graph, neighbor, target, remaining_hops - 1, memo))
+            .sum();
+
+        memo.insert(key, result);
+        result
+    }
+
+    count(graph, start, target, max_hops, &mut HashMap::new())
+}
+
 fn count_paths_with_required<'a>(
     graph: &Graph<'a>,
     current: &'a str,
     target: &str,
-    state: u8,
-    memo: &mut HashMap<(&'a str, u8), u64>,
+    required: &RequiredSet,
+    state: u32,
+    memo: &mut HashMap<(&'a str, u32), u64>,
 ) -> u64 {
     if current == target {
-        return if state == BOTH_REQUIRED { 1 } else { 0 };
+        return if state == required.full_mask() { 1 } else { 0 };
     }
 
     let key = (current, state);
@@ -69,10 +277,8 @@ fn count_paths_with_required<'a>(
             neighbors
                 .iter()
                 .map(|&neighbor| {
-                    let new_state = state
-                        | if neighbor == "dac" { DAC_BIT } else { 0 }
-                        | if neighbor == "fft" { FFT_BIT } else { 0 };
-                    count_paths_with_required(graph, neighbor, target, new_state, memo)
+                    let new_state = state | required.bit(neighbor);
+                    count_paths_with_required(graph, neighbor, target, required, new_state, memo)
                 })
                 .sum()
         })
@@ -83,16 +289,22 @@ fn count_paths_with_required<'a>(
 }
 
 pub fn part_one(input: &str) -> Option<u64> {
-    let graph = parse_graph(input);
+    let graph = try_parse_graph(input).ok()?;
     let mut memo = HashMap::new();
     Some(count_paths(&graph, "you", "out", &mut memo))
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
-    let graph = parse_graph(input);
+    let graph = try_parse_graph(input).ok()?;
+    let required = RequiredSet::new(vec!["dac", "fft"]);
     let mut memo = HashMap::new();
     Some(count_paths_with_required(
-        &graph, "svr", "out", 0, &mut memo,
+        &graph,
+        "svr",
+        "out",
+        &required,
+        required.bit("svr"),
+        &mut memo,
     ))
 }
 
@@ -113,4 +325,96 @@ mod tests {
         ));
         assert_eq!(result, Some(2));
     }
+
+    #[test]
+    fn test_single_required_node() {
+        let input = advent_of_code::template::read_file_part("examples", DAY, 2);
+        let graph = try_parse_graph(&input).unwrap();
+        let required = RequiredSet::new(vec!["fft"]);
+        assert_eq!(required.full_mask(), 1);
+
+        let count =
+            count_paths_with_required(&graph, "svr", "out", &required, 0, &mut HashMap::new());
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn test_reachable_from() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let graph = try_parse_graph(&input).unwrap();
+        assert_eq!(
+            reachable_from(&graph, "you"),
+            HashSet::from(["you", "bbb", "ccc", "ddd", "eee", "fff", "ggg", "out"])
+        );
+
+        let cyclic = try_parse_graph("a: b\nb: c\nc: a").unwrap();
+        assert_eq!(reachable_from(&cyclic, "b"), HashSet::from(["a", "b", "c"]));
+    }
+
+    #[test]
+    fn test_count_paths_multi() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let graph = try_parse_graph(&input).unwrap();
+        let counts = count_paths_multi(&graph, "you", &["out", "ddd"]);
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["out"], 5);
+        assert_eq!(counts["ddd"], 2);
+    }
+
+    #[test]
+    fn test_count_paths_through() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let graph = try_parse_graph(&input).unwrap();
+
+        // One way to ccc, then three on to out
+        assert_eq!(count_paths_through(&graph, "you", "out", "ccc"), 3);
+        assert_eq!(count_paths_through(&graph, "you", "out", "ddd"), 2);
+        assert_eq!(count_paths_through(&graph, "you", "out", "hhh"), 0);
+        assert_eq!(count_paths_through(&graph, "you", "out", "zzz"), 0);
+    }
+
+    #[test]
+    fn test_count_paths_excluding() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let graph = try_parse_graph(&input).unwrap();
+        assert_eq!(
+            count_paths_excluding(&graph, "you", "out", &HashSet::new()),
+            5
+        );
+
+        // ccc -> ddd carries one of the five paths
+        let forbidden = HashSet::from([("ccc", "ddd")]);
+        assert_eq!(count_paths_excluding(&graph, "you", "out", &forbidden), 4);
+
+        let forbidden = HashSet::from([("you", "bbb"), ("you", "ccc")]);
+        assert_eq!(count_paths_excluding(&graph, "you", "out", &forbidden), 0);
+    }
+
+    #[test]
+    fn test_count_paths_max_hops() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let graph = try_parse_graph(&input).unwrap();
+
+        // Three paths take 3 hops, the two through ddd and ggg take 4
+        assert_eq!(count_paths_max_hops(&graph, "you", "out", 2), 0);
+        assert_eq!(count_paths_max_hops(&graph, "you", "out", 3), 3);
+        assert_eq!(count_paths_max_hops(&graph, "you", "out", 4), 5);
+        assert_eq!(count_paths_max_hops(&graph, "you", "out", 100), 5);
+
+        let cyclic = try_parse_graph("a: b\nb: a out").unwrap();
+        assert_eq!(count_paths_max_hops(&cyclic, "a", "out", 5), 2);
+    }
+
+    #[test]
+    fn test_parse_skips_comments_and_blank_lines() {
+        let graph = try_parse_graph("# header\nyou: aaa bbb\n\n  # indented\naaa: out\nbbb: out\n")
+            .unwrap();
+        assert_eq!(graph.len(), 3);
+        assert_eq!(count_paths(&graph, "you", "out", &mut HashMap::new()), 2);
+    }
+
+    #[test]
+    fn test_parse_rejects_line_without_colon() {
+        assert!(try_parse_graph("you: aaa\naaa out\n").is_err());
+    }
 }
@@ -1,5 +1,8 @@
 advent_of_code::solution!(12);
 
+use advent_of_code::geometry::{Transform, transform_coord};
+use std::collections::HashSet;
+
 type Coord = (i32, i32);
 type Shape = Vec<Coord>;
 
@@ -22,22 +25,26 @@ impl Present {
         Present { cells }
     }
 
-    fn rotations_and_flips(&self) -> Vec<Shape> {
-        let transformations: [fn(Coord) -> Coord; 8] = [
-            |(x, y)| (x, y),
-            |(x, y)| (-y, x),
-            |(x, y)| (-x, -y),
-            |(x, y)| (y, -x),
-            |(x, y)| (-x, y),
-            |(x, y)| (y, x),
-            |(x, y)| (x, -y),
-            |(x, y)| (-y, -x),
-        ];
+    /// Width and height of the smallest box around the present's cells
+    fn bounding_box(&self) -> (usize, usize) {
+        let extent = |coord: fn(&Coord) -> i32| {
+            let min = self.cells.iter().map(coord).min();
+            let max = self.cells.iter().map(coord).max();
+            min.zip(max)
+                .map_or(0, |(min, max)| (max - min + 1) as usize)
+        };
+        (extent(|&(x, _)| x), extent(|&(_, y)| y))
+    }
 
+    fn rotations_and_flips(&self) -> Vec<Shape> {
         let mut unique_variants = Vec::with_capacity(8);
 
-        for &transform in &transformations {
-            let transformed: Shape = self.cells.iter().map(|&c| transform(c)).collect();
+        for transform in Transform::all() {
+            let transformed: Shape = self
+                .cells
+                .iter()
+                .map(|&c| transform_coord(c, transform))
+                .collect();
             let normalized = normalize(&transformed);
 
             if !unique_variants.contains(&normalized) {
@@ -73,6 +80,26 @@ struct Region {
     required: Vec<usize>,
 }
 
+impl Region {
+    /// Whether every required present fits inside the region in some rotation, checked on
+    /// bounding boxes alone
+    fn fits_bounding_boxes(&self, shapes: &[Present]) -> bool {
+        self.pieces().into_iter().all(|shape_idx| {
+            let (w, h) = shapes[shape_idx].bounding_box();
+            (w <= self.width && h <= self.height) || (h <= self.width && w <= self.height)
+        })
+    }
+
+    /// One shape index per required piece, with copies of the same shape adjacent
+    fn pieces(&self) -> Vec<usize> {
+        self.required
+            .iter()
+            .enumerate()
+            .flat_map(|(shape_idx, &count)| std::iter::repeat_n(shape_idx, count))
+            .collect()
+    }
+}
+
 fn parse_input(input: &str) -> (Vec<Present>, Vec<Region>) {
     let lines: Vec<&str> = input.lines().collect();
     let mut i = 0;
@@ -120,6 +147,9 @@ fn parse_input(input: &str) -> (Vec<Present>, Vec<Region>) {
     (shapes, regions)
 }
 
+/// Largest region the solver will allocate a grid for
+const MAX_GRID_CELLS: usize = 1 << 20;
+
 struct Grid {
     width: usize,
     height: usize,
@@ -129,16 +159,20 @@ struct Grid {
 }
 
 impl Grid {
-    fn new(width: usize, height: usize) -> Self {
-        let total_cells = width * height;
+    /// Returns None for regions larger than `MAX_GRID_CELLS`, so malformed input
+    /// can't trigger a huge allocation
+    fn new(width: usize, height: usize) -> Option<Self> {
+        let total_cells = width
+            .checked_mul(height)
+            .filter(|&cells| cells <= MAX_GRID_CELLS)?;
         let num_words = total_cells.div_ceil(64);
-        Grid {
+        Some(Grid {
             width,
             height,
             occupied: vec![0; num_words],
             total_cells,
             filled_cells: 0,
-        }
+        })
     }
 
     #[inline]
@@ -191,7 +225,73 @@ impl Grid {
     }
 }
 
-/// Backtracking solver to fit all presents into the grid
+/// A piece on the grid: its shape, which variant of that shape, and the anchor offset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Placement {
+    shape_idx: usize,
+    variant_idx: usize,
+    x: i32,
+    y: i32,
+}
+
+/// Order in which the backtracker tries placements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum PlacementStrategy {
+    /// Place pieces one at a time, scanning anchor positions row by row
+    #[default]
+    RowMajor,
+    /// Always fill the first empty cell, trying only pieces that cover it
+    /// (or leaving it empty if the region has spare cells)
+    #[allow(dead_code)]
+    MostConstrained,
+}
+
+/// Settings for the packing backtracker
+#[derive(Debug, Clone, Copy, Default)]
+struct SolverOptions {
+    strategy: PlacementStrategy,
+    /// Only accept packings whose placed cells form a single edge-connected group
+    require_connected_pieces: bool,
+}
+
+impl SolverOptions {
+    /// Whether a complete set of placements satisfies the options
+    fn accepts(&self, variants_list: &[Vec<Shape>], placements: &[Placement]) -> bool {
+        !self.require_connected_pieces || pieces_connected(variants_list, placements)
+    }
+}
+
+/// Whether the cells covered by `placements` form a single edge-connected group
+fn pieces_connected(variants_list: &[Vec<Shape>], placements: &[Placement]) -> bool {
+    let cells: HashSet<Coord> = placements
+        .iter()
+        .flat_map(|p| {
+            variants_list[p.shape_idx][p.variant_idx]
+                .iter()
+                .map(move |&(dx, dy)| (p.x + dx, p.y + dy))
+        })
+        .collect();
+
+    let Some(&start) = cells.iter().next() else {
+        return true;
+    };
+    let mut seen = HashSet::from([start]);
+    let mut stack = vec![start];
+
+    while let Some((x, y)) = stack.pop() {
+        for next in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+            if cells.contains(&next) && seen.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+
+    seen.len() == cells.len()
+}
+
+/// Backtracking solver to fit all presents into the grid.
+/// On success the grid is left filled and `placements` holds the pieces in placement order.
+#[allow(clippy::too_many_arguments)]
 fn can_fit_presents(
     grid: &mut Grid,
     variants_list: &[Vec<Shape>],
@@ -199,10 +299,38 @@ fn can_fit_presents(
     piece_idx: usize,
     pieces_remaining: usize,
     total_cells_needed: usize,
+    options: SolverOptions,
+    placements: &mut Vec<Placement>,
 ) -> bool {
     // Base case: all pieces placed successfully
     if piece_idx >= pieces_to_place.len() {
-        return true;
+        return options.accepts(variants_list, placements);
+    }
+
+    if options.strategy == PlacementStrategy::MostConstrained {
+        let Some(slack) = (grid.total_cells - grid.filled_cells).checked_sub(total_cells_needed)
+        else {
+            return false;
+        };
+        let mut remaining: Vec<usize> = pieces_to_place
+            .iter()
+            .enumerate()
+            .map(|(idx, &(_, count))| match idx.cmp(&piece_idx) {
+                std::cmp::Ordering::Less => 0,
+                std::cmp::Ordering::Equal => pieces_remaining,
+                std::cmp::Ordering::Greater => count,
+            })
+            .collect();
+        return fill_first_empty(
+            grid,
+            variants_list,
+            pieces_to_place,
+            &mut remaining,
+            0,
+            slack,
+            options,
+            placements,
+        );
     }
 
     // Early termination: impossible to fit remaining pieces
@@ -217,9 +345,15 @@ fn can_fit_presents(
     // Try placing one copy of the current shape at each position
     for y in 0..grid.height as i32 {
         for x in 0..grid.width as i32 {
-            for variant in shape_variants {
+            for (variant_idx, variant) in shape_variants.iter().enumerate() {
                 if grid.can_place(variant, x, y) {
                     grid.place(variant, x, y);
+                    placements.push(Placement {
+                        shape_idx,
+                        variant_idx,
+                        x,
+                        y,
+                    });
 
                     let next_cells_needed = total_cells_needed - cells_per_piece;
                     let success = if pieces_remaining == 1 {
@@ -235,6 +369,8 @@ fn can_fit_presents(
                                 0
                             },
                             next_cells_needed,
+                            options,
+                            placements,
                         )
                     } else {
                         // Place another copy of the same shape
@@ -245,14 +381,17 @@ fn can_fit_presents(
                             piece_idx,
                             pieces_remaining - 1,
                             next_cells_needed,
+                            options,
+                            placements,
                         )
                     };
 
-                    grid.remove(variant, x, y);
-
                     if success {
                         return true;
                     }
+
+                    grid.remove(variant, x, y);
+                    placements.pop();
                 }
             }
         }
@@ -261,58 +400,496 @@ fn can_fit_presents(
     false
 }
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let (shapes, regions) = parse_input(input);
+/// Most-constrained-cell backtracker: find the first empty cell at or after `start` and
+/// either cover it with a remaining piece or, while `slack` allows, leave it empty.
+///
+/// `remaining[i]` counts the copies of `pieces_to_place[i]` still to be placed.
+#[allow(clippy::too_many_arguments)]
+fn fill_first_empty(
+    grid: &mut Grid,
+    variants_list: &[Vec<Shape>],
+    pieces_to_place: &[(usize, usize)],
+    remaining: &mut [usize],
+    start: usize,
+    slack: usize,
+    options: SolverOptions,
+    placements: &mut Vec<Placement>,
+) -> bool {
+    if remaining.iter().all(|&count| count == 0) {
+        return options.accepts(variants_list, placements);
+    }
 
-    // Precompute all shape variants (rotations/flips) once
-    let variants_list: Vec<Vec<Shape>> = shapes.iter().map(|s| s.rotations_and_flips()).collect();
+    let Some(cell) = (start..grid.total_cells)
+        .find(|&idx| !grid.is_occupied(idx % grid.width, idx / grid.width))
+    else {
+        return false;
+    };
+    let (cx, cy) = ((cell % grid.width) as i32, (cell / grid.width) as i32);
 
-    let mut valid_regions = 0;
+    for (i, &(shape_idx, _)) in pieces_to_place.iter().enumerate() {
+        if remaining[i] == 0 {
+            continue;
+        }
 
-    for region in regions {
-        // Build list of pieces to place: (shape_idx, count)
-        let mut pieces_to_place: Vec<(usize, usize)> = region
-            .required
-            .iter()
-            .enumerate()
-            .filter(|&(_, &cnt)| cnt > 0)
-            .map(|(idx, &cnt)| (idx, cnt))
-            .collect();
+        for (variant_idx, variant) in variants_list[shape_idx].iter().enumerate() {
+            // Anchor the variant so each of its cells in turn lands on the target cell
+            for &(dx, dy) in variant {
+                let (x, y) = (cx - dx, cy - dy);
+                if !grid.can_place(variant, x, y) {
+                    continue;
+                }
 
-        // Heuristic: place most numerous pieces first (better pruning)
-        pieces_to_place.sort_unstable_by_key(|&(_, cnt)| std::cmp::Reverse(cnt));
+                grid.place(variant, x, y);
+                placements.push(Placement {
+                    shape_idx,
+                    variant_idx,
+                    x,
+                    y,
+                });
+                remaining[i] -= 1;
 
-        // Calculate total cells needed for early termination
-        let total_cells_needed: usize = pieces_to_place
-            .iter()
-            .map(|&(shape_idx, count)| variants_list[shape_idx][0].len() * count)
-            .sum();
+                if fill_first_empty(
+                    grid,
+                    variants_list,
+                    pieces_to_place,
+                    remaining,
+                    cell + 1,
+                    slack,
+                    options,
+                    placements,
+                ) {
+                    return true;
+                }
+
+                remaining[i] += 1;
+                placements.pop();
+                grid.remove(variant, x, y);
+            }
+        }
+    }
+
+    if slack == 0 {
+        return false;
+    }
 
-        // Quick check: can't fit if more cells needed than available
-        if total_cells_needed > region.width * region.height {
+    // Leave the cell empty by blocking it for the rest of the search
+    grid.set_cell(cx as usize, cy as usize, true);
+    let success = fill_first_empty(
+        grid,
+        variants_list,
+        pieces_to_place,
+        remaining,
+        cell + 1,
+        slack - 1,
+        options,
+        placements,
+    );
+    grid.set_cell(cx as usize, cy as usize, false);
+    success
+}
+
+/// Exhaustively collect every complete packing as a per-cell label grid
+/// (0 = empty, otherwise shape index + 1).
+///
+/// `pieces` lists one shape index per piece, with identical shapes adjacent. Copies of the
+/// same shape are placed in increasing slot order so each arrangement is visited once.
+fn collect_labeled_packings(
+    grid: &mut Grid,
+    labels: &mut [usize],
+    variants_list: &[Vec<Shape>],
+    pieces: &[usize],
+    min_slot: usize,
+    packings: &mut HashSet<Vec<usize>>,
+) {
+    let Some((&shape_idx, rest)) = pieces.split_first() else {
+        packings.insert(labels.to_vec());
+        return;
+    };
+
+    let variants = &variants_list[shape_idx];
+    let num_slots = grid.width * grid.height * variants.len();
+
+    for slot in min_slot..num_slots {
+        let (pos, variant) = (slot / variants.len(), &variants[slot % variants.len()]);
+        let (x, y) = ((pos % grid.width) as i32, (pos / grid.width) as i32);
+
+        if !grid.can_place(variant, x, y) {
             continue;
         }
 
-        let mut grid = Grid::new(region.width, region.height);
-        let initial_count = if !pieces_to_place.is_empty() {
-            pieces_to_place[0].1
+        grid.place(variant, x, y);
+        for &(dx, dy) in variant {
+            labels[(y + dy) as usize * grid.width + (x + dx) as usize] = shape_idx + 1;
+        }
+
+        let next_min_slot = if rest.first() == Some(&shape_idx) {
+            slot + 1
         } else {
             0
         };
+        collect_labeled_packings(grid, labels, variants_list, rest, next_min_slot, packings);
 
-        if can_fit_presents(
-            &mut grid,
-            &variants_list,
-            &pieces_to_place,
-            0,
-            initial_count,
-            total_cells_needed,
-        ) {
-            valid_regions += 1;
+        grid.remove(variant, x, y);
+        for &(dx, dy) in variant {
+            labels[(y + dy) as usize * grid.width + (x + dx) as usize] = 0;
+        }
+    }
+}
+
+/// All distinct complete packings of a region, keyed by which shape fills each cell
+fn labeled_packings(region: &Region, variants_list: &[Vec<Shape>]) -> HashSet<Vec<usize>> {
+    let pieces = region.pieces();
+    let Some(mut grid) = Grid::new(region.width, region.height) else {
+        return HashSet::new();
+    };
+    let mut labels = vec![0; grid.total_cells];
+    let mut packings = HashSet::new();
+    collect_labeled_packings(
+        &mut grid,
+        &mut labels,
+        variants_list,
+        &pieces,
+        0,
+        &mut packings,
+    );
+    packings
+}
+
+/// Count distinct final grids by occupancy only
+#[allow(dead_code)]
+fn count_packings(region: &Region, variants_list: &[Vec<Shape>]) -> u64 {
+    labeled_packings(region, variants_list)
+        .into_iter()
+        .map(|labels| labels.into_iter().map(|l| l != 0).collect::<Vec<_>>())
+        .collect::<HashSet<_>>()
+        .len() as u64
+}
+
+/// Count distinct final grids, telling apart arrangements that fill the same cells
+/// with different pieces
+#[allow(dead_code)]
+fn count_labeled_packings(region: &Region, variants_list: &[Vec<Shape>]) -> u64 {
+    labeled_packings(region, variants_list).len() as u64
+}
+
+/// Maximum number of search steps `best_fill_fraction` will take
+const FILL_SEARCH_BUDGET: usize = 100_000;
+
+/// Place pieces in order, returning the most cells any partial arrangement filled
+fn best_fill_search(
+    grid: &mut Grid,
+    variants_list: &[Vec<Shape>],
+    pieces: &[usize],
+    min_slot: usize,
+    budget: &mut usize,
+) -> usize {
+    let mut best = grid.filled_cells;
+    let Some((&shape_idx, rest)) = pieces.split_first() else {
+        return best;
+    };
+
+    let variants = &variants_list[shape_idx];
+    let num_slots = grid.width * grid.height * variants.len();
+
+    for slot in min_slot..num_slots {
+        if *budget == 0 || best == grid.total_cells {
+            break;
+        }
+        *budget -= 1;
+
+        let (pos, variant) = (slot / variants.len(), &variants[slot % variants.len()]);
+        let (x, y) = ((pos % grid.width) as i32, (pos / grid.width) as i32);
+
+        if grid.can_place(variant, x, y) {
+            grid.place(variant, x, y);
+            let next_min_slot = if rest.first() == Some(&shape_idx) {
+                slot + 1
+            } else {
+                0
+            };
+            best = best.max(best_fill_search(
+                grid,
+                variants_list,
+                rest,
+                next_min_slot,
+                budget,
+            ));
+            grid.remove(variant, x, y);
+        }
+    }
+
+    best
+}
+
+/// The largest fraction of a region's cells any (partial) placement of its pieces
+/// reached, as a heuristic for how close an unsolvable region came.
+///
+/// The search is capped at `FILL_SEARCH_BUDGET` steps, so this is a lower bound.
+#[allow(dead_code)]
+fn best_fill_fraction(region: &Region, variants_list: &[Vec<Shape>]) -> f64 {
+    let Some(mut grid) = Grid::new(region.width, region.height) else {
+        return 0.0;
+    };
+    if grid.total_cells == 0 {
+        return 0.0;
+    }
+
+    let mut budget = FILL_SEARCH_BUDGET;
+    let filled = best_fill_search(&mut grid, variants_list, &region.pieces(), 0, &mut budget);
+    filled as f64 / grid.total_cells as f64
+}
+
+/// Maximum number of search steps `min_overlap_to_place_all` will take
+const OVERLAP_SEARCH_BUDGET: usize = 1_000_000;
+
+/// State of the overlap-minimizing search: how many placed pieces cover each cell, and the
+/// best total overlap found so far
+struct OverlapSearch<'a> {
+    coverage: Vec<u8>,
+    width: usize,
+    height: usize,
+    variants_list: &'a [Vec<Shape>],
+    lower_bound: usize,
+    best: usize,
+    budget: usize,
+}
+
+impl OverlapSearch<'_> {
+    /// Place every piece in order, allowing overlaps, and lower `best` to the least total
+    /// overlap found.
+    ///
+    /// Copies of the same shape take slots in non-decreasing order, so each multiset of
+    /// placements is tried once; repeating a slot is allowed, since pieces may overlap.
+    fn search(&mut self, pieces: &[usize], min_slot: usize, overlap: usize) {
+        let Some((&shape_idx, rest)) = pieces.split_first() else {
+            self.best = self.best.min(overlap);
+            return;
+        };
+
+        let num_variants = self.variants_list[shape_idx].len();
+        let num_slots = self.width * self.height * num_variants;
+
+        for slot in min_slot..num_slots {
+            if self.budget == 0 || self.best <= self.lower_bound {
+                break;
+            }
+            self.budget -= 1;
+
+            let Some(cells) = self.cells(shape_idx, slot) else {
+                continue;
+            };
+
+            let added = cells.iter().filter(|&&idx| self.coverage[idx] > 0).count();
+            if overlap + added >= self.best {
+                continue;
+            }
+
+            cells.iter().for_each(|&idx| self.coverage[idx] += 1);
+            let next_min_slot = if rest.first() == Some(&shape_idx) {
+                slot
+            } else {
+                0
+            };
+            self.search(rest, next_min_slot, overlap + added);
+            cells.iter().for_each(|&idx| self.coverage[idx] -= 1);
+        }
+    }
+
+    /// Cell indices a shape covers at a (position, variant) slot, or None if it sticks out
+    fn cells(&self, shape_idx: usize, slot: usize) -> Option<Vec<usize>> {
+        let variants = &self.variants_list[shape_idx];
+        let (pos, variant) = (slot / variants.len(), &variants[slot % variants.len()]);
+        let (x, y) = (pos % self.width, pos / self.width);
+        variant
+            .iter()
+            .map(|&(dx, dy)| {
+                let (nx, ny) = (x + dx as usize, y + dy as usize);
+                (nx < self.width && ny < self.height).then_some(ny * self.width + nx)
+            })
+            .collect()
+    }
+}
+
+/// The fewest doubly-covered cells (counting a cell under n pieces n - 1 times) needed to
+/// fit all of a region's pieces inside it if they may overlap, as a measure of how tight
+/// the region is. 0 means an exact packing exists.
+///
+/// Returns `usize::MAX` if the region is too large to allocate or some piece cannot lie
+/// inside it at all. The search is capped at `OVERLAP_SEARCH_BUDGET` steps, so this is
+/// an upper bound.
+#[allow(dead_code)]
+fn min_overlap_to_place_all(region: &Region, variants_list: &[Vec<Shape>]) -> usize {
+    let Some(grid) = Grid::new(region.width, region.height) else {
+        return usize::MAX;
+    };
+    let pieces = region.pieces();
+    let needed: usize = pieces.iter().map(|&idx| variants_list[idx][0].len()).sum();
+
+    // Every cell past the region's area has to land on an occupied one
+    let lower_bound = needed.saturating_sub(grid.total_cells);
+    if lower_bound == 0 && find_packing(region, variants_list).is_some() {
+        return 0;
+    }
+
+    let mut search = OverlapSearch {
+        coverage: vec![0; grid.total_cells],
+        width: grid.width,
+        height: grid.height,
+        variants_list,
+        lower_bound,
+        best: usize::MAX,
+        budget: OVERLAP_SEARCH_BUDGET,
+    };
+    search.search(&pieces, 0, 0);
+    search.best
+}
+
+/// Find one packing of all of a region's required presents
+fn find_packing(region: &Region, variants_list: &[Vec<Shape>]) -> Option<Vec<Placement>> {
+    find_packing_with(region, variants_list, SolverOptions::default())
+}
+
+/// Find one packing of all of a region's required presents using the given solver options
+fn find_packing_with(
+    region: &Region,
+    variants_list: &[Vec<Shape>],
+    options: SolverOptions,
+) -> Option<Vec<Placement>> {
+    // Skip regions too large to allocate
+    let mut grid = Grid::new(region.width, region.height)?;
+
+    let mut placements = Vec::new();
+    can_fit_presents_from(
+        &mut grid,
+        variants_list,
+        &region.required,
+        options,
+        &mut placements,
+    )
+    .then_some(placements)
+}
+
+/// Fit `required[i]` copies of each shape around the cells already occupied in `grid`.
+/// On success the grid is left filled and `placements` holds the newly placed pieces.
+fn can_fit_presents_from(
+    grid: &mut Grid,
+    variants_list: &[Vec<Shape>],
+    required: &[usize],
+    options: SolverOptions,
+    placements: &mut Vec<Placement>,
+) -> bool {
+    // Build list of pieces to place: (shape_idx, count)
+    let mut pieces_to_place: Vec<(usize, usize)> = required
+        .iter()
+        .enumerate()
+        .filter(|&(_, &cnt)| cnt > 0)
+        .map(|(idx, &cnt)| (idx, cnt))
+        .collect();
+
+    // Heuristic: place most numerous pieces first (better pruning)
+    pieces_to_place.sort_unstable_by_key(|&(_, cnt)| std::cmp::Reverse(cnt));
+
+    // Calculate total cells needed for early termination
+    let total_cells_needed: usize = pieces_to_place
+        .iter()
+        .map(|&(shape_idx, count)| variants_list[shape_idx][0].len() * count)
+        .sum();
+
+    // Cells may have been set directly, so recount what's already occupied
+    grid.filled_cells = grid
+        .occupied
+        .iter()
+        .map(|word| word.count_ones() as usize)
+        .sum();
+
+    // Quick check: can't fit if more cells needed than are still free
+    if total_cells_needed > grid.total_cells - grid.filled_cells {
+        return false;
+    }
+
+    let initial_count = if !pieces_to_place.is_empty() {
+        pieces_to_place[0].1
+    } else {
+        0
+    };
+
+    can_fit_presents(
+        grid,
+        variants_list,
+        &pieces_to_place,
+        0,
+        initial_count,
+        total_cells_needed,
+        options,
+        placements,
+    )
+}
+
+/// How many of each shape a successful packing placed, or None if the region is unsolvable
+#[allow(dead_code)]
+fn placement_summary(region: &Region, variants_list: &[Vec<Shape>]) -> Option<Vec<usize>> {
+    let placements = find_packing(region, variants_list)?;
+    let mut counts = vec![0; variants_list.len()];
+    for placement in placements {
+        counts[placement.shape_idx] += 1;
+    }
+    Some(counts)
+}
+
+/// Render the packing found by the default row-major search as an ASCII grid, marking
+/// each piece's cells with its own letter (A, B, C, ... in placement order, wrapping
+/// after Z) and empty cells with `.`
+#[allow(dead_code)]
+fn render_first_packing(region: &Region, variants_list: &[Vec<Shape>]) -> Option<String> {
+    let placements = find_packing(region, variants_list)?;
+    let mut cells = vec![vec![b'.'; region.width]; region.height];
+
+    for (piece, placement) in placements.iter().enumerate() {
+        let letter = b'A' + (piece % 26) as u8;
+        for &(dx, dy) in &variants_list[placement.shape_idx][placement.variant_idx] {
+            cells[(placement.y + dy) as usize][(placement.x + dx) as usize] = letter;
         }
     }
 
-    Some(valid_regions)
+    Some(
+        cells
+            .into_iter()
+            .map(|row| String::from_utf8(row).unwrap() + "\n")
+            .collect(),
+    )
+}
+
+/// 0-based indices of the regions that cannot be packed, including those skipped as too
+/// large to allocate or with more present cells than the region has
+#[allow(dead_code)]
+fn failing_regions(input: &str) -> Vec<usize> {
+    let (shapes, regions) = parse_input(input);
+    let variants_list: Vec<Vec<Shape>> = shapes.iter().map(|s| s.rotations_and_flips()).collect();
+
+    regions
+        .iter()
+        .enumerate()
+        .filter(|(_, region)| {
+            !region.fits_bounding_boxes(&shapes) || find_packing(region, &variants_list).is_none()
+        })
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+pub fn part_one(input: &str) -> Option<u64> {
+    let (shapes, regions) = parse_input(input);
+
+    // Precompute all shape variants (rotations/flips) once
+    let variants_list: Vec<Vec<Shape>> = shapes.iter().map(|s| s.rotations_and_flips()).collect();
+
+    // Regions a present can't fit in, however it's turned, are skipped before searching
+    let valid_regions = regions
+        .iter()
+        .filter(|region| region.fits_bounding_boxes(&shapes))
+        .filter(|region| find_packing(region, &variants_list).is_some())
+        .count();
+
+    Some(valid_regions as u64)
 }
 
 pub fn part_two(_input: &str) -> Option<u64> {
@@ -334,4 +911,293 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, None);
     }
+
+    #[test]
+    fn test_rotations_and_flips() {
+        let variant_count = |lines: &[&str]| Present::from_lines(lines).rotations_and_flips().len();
+        assert_eq!(variant_count(&["#"]), 1);
+        assert_eq!(variant_count(&["##"]), 2);
+        assert_eq!(variant_count(&["##", "##"]), 1);
+        assert_eq!(variant_count(&["##", "#."]), 4);
+        assert_eq!(variant_count(&[".##", "##."]), 4);
+        assert_eq!(variant_count(&["###", "#.."]), 8);
+
+        let variants = Present::from_lines(&["##", "#."]).rotations_and_flips();
+        assert_eq!(variants[0], vec![(0, 0), (0, 1), (1, 0)]);
+        assert_eq!(variants[1], vec![(0, 0), (1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn test_oversized_region_is_skipped() {
+        assert!(Grid::new(1_000_000, 1_000_000).is_none());
+        assert!(Grid::new(usize::MAX, 2).is_none());
+
+        let input = "0:\n#\n\n1000000x1000000: 1\n2x1: 2\n";
+        assert_eq!(part_one(input), Some(1));
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let input = "0:\n#\n#\n#\n#\n\n1:\n.#\n##\n\n3x3: 1 0\n4x1: 1 0\n1x4: 1 1\n";
+        let (shapes, regions) = parse_input(input);
+        assert_eq!(shapes[0].bounding_box(), (1, 4));
+        assert_eq!(shapes[1].bounding_box(), (2, 2));
+
+        // The tall piece doesn't fit 3x3 but fits the short region lying down, while the
+        // 2x2 piece is too wide for 1x4
+        assert!(!regions[0].fits_bounding_boxes(&shapes));
+        assert!(regions[1].fits_bounding_boxes(&shapes));
+        assert!(!regions[2].fits_bounding_boxes(&shapes));
+        assert_eq!(part_one(input), Some(1));
+        assert_eq!(failing_regions(input), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_failing_regions() {
+        // Swap the example's slow unsolvable region for a small one, between the solvable ones
+        let example = advent_of_code::template::read_file("examples", DAY);
+        let (shapes, _) = example.split_once("4x4").unwrap();
+        let input = format!("{shapes}4x4: 0 0 0 0 2 0\n3x3: 2 0 0 0 0 0\n12x5: 1 0 1 0 2 2\n");
+
+        let failing = failing_regions(&input);
+        assert_eq!(failing, vec![1]);
+        assert_eq!(part_one(&input), Some(2));
+        assert_eq!(part_one(&input).unwrap() as usize + failing.len(), 3);
+    }
+
+    #[test]
+    fn test_min_overlap_to_place_all() {
+        let (shapes, regions) = parse_input(&advent_of_code::template::read_file("examples", DAY));
+        let variants_list: Vec<Vec<Shape>> =
+            shapes.iter().map(|s| s.rotations_and_flips()).collect();
+        assert_eq!(min_overlap_to_place_all(&regions[0], &variants_list), 0);
+
+        // Two 7-cell pieces in 9 cells: turning one around makes their gaps disjoint,
+        // so they share exactly 5 cells
+        let over_full = Region {
+            width: 3,
+            height: 3,
+            required: vec![2, 0, 0, 0, 0, 0],
+        };
+        assert_eq!(min_overlap_to_place_all(&over_full, &variants_list), 5);
+
+        // Stacking copies on the same spot is allowed, so any number of pieces can be placed
+        let crowded = |copies| Region {
+            width: 3,
+            height: 3,
+            required: vec![copies, 0, 0, 0, 0, 0],
+        };
+        assert_eq!(min_overlap_to_place_all(&crowded(8), &variants_list), 47);
+        assert_eq!(min_overlap_to_place_all(&crowded(9), &variants_list), 54);
+
+        // Two H shapes have room for their 14 cells in 15, but can't avoid sharing a row;
+        // the best is a sideways H whose row 0 puts only its two ends on the other's bar
+        let tight = Region {
+            width: 3,
+            height: 5,
+            required: vec![0, 0, 0, 0, 0, 2],
+        };
+        assert_eq!(min_overlap_to_place_all(&tight, &variants_list), 2);
+
+        let too_small = Region {
+            width: 2,
+            height: 2,
+            required: vec![1, 0, 0, 0, 0, 0],
+        };
+        assert_eq!(
+            min_overlap_to_place_all(&too_small, &variants_list),
+            usize::MAX
+        );
+    }
+
+    #[test]
+    fn test_placement_strategies_agree() {
+        let (shapes, regions) = parse_input(&advent_of_code::template::read_file("examples", DAY));
+        let variants_list: Vec<Vec<Shape>> =
+            shapes.iter().map(|s| s.rotations_and_flips()).collect();
+
+        // The example's unsolvable region takes too long to exhaust in a debug build,
+        // so use a small unsolvable region alongside the two solvable ones
+        let impossible = Region {
+            width: 3,
+            height: 3,
+            required: vec![2, 0, 0, 0, 0, 0],
+        };
+
+        let solvable = |region: &Region, strategy| {
+            let options = SolverOptions {
+                strategy,
+                ..SolverOptions::default()
+            };
+            find_packing_with(region, &variants_list, options).is_some()
+        };
+        for strategy in [
+            PlacementStrategy::RowMajor,
+            PlacementStrategy::MostConstrained,
+        ] {
+            assert!(solvable(&regions[0], strategy));
+            assert!(solvable(&regions[1], strategy));
+            assert!(!solvable(&impossible, strategy));
+        }
+    }
+
+    #[test]
+    fn test_require_connected_pieces() {
+        let variants_list = vec![
+            Present::from_lines(&["#"]).rotations_and_flips(),
+            Present::from_lines(&["#.#"]).rotations_and_flips(),
+        ];
+        let region = |required| Region {
+            width: 3,
+            height: 1,
+            required,
+        };
+
+        for strategy in [
+            PlacementStrategy::RowMajor,
+            PlacementStrategy::MostConstrained,
+        ] {
+            let connected = SolverOptions {
+                strategy,
+                require_connected_pieces: true,
+            };
+
+            // Two monominoes can sit side by side
+            let placements =
+                find_packing_with(&region(vec![2, 0]), &variants_list, connected).unwrap();
+            assert!(pieces_connected(&variants_list, &placements));
+
+            // A piece with a gap can never form a single group on its own
+            let gapped = region(vec![0, 1]);
+            assert!(find_packing(&gapped, &variants_list).is_some());
+            assert!(find_packing_with(&gapped, &variants_list, connected).is_none());
+        }
+    }
+
+    #[test]
+    fn test_placement_summary() {
+        let (shapes, regions) = parse_input(&advent_of_code::template::read_file("examples", DAY));
+        let variants_list: Vec<Vec<Shape>> =
+            shapes.iter().map(|s| s.rotations_and_flips()).collect();
+
+        for region in &regions[..2] {
+            assert_eq!(
+                placement_summary(region, &variants_list).as_ref(),
+                Some(&region.required)
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_first_packing() {
+        let (shapes, regions) = parse_input(&advent_of_code::template::read_file("examples", DAY));
+        let variants_list: Vec<Vec<Shape>> =
+            shapes.iter().map(|s| s.rotations_and_flips()).collect();
+
+        let rendered = render_first_packing(&regions[0], &variants_list);
+        assert_eq!(rendered.as_deref(), Some("AAA.\nABBB\nAAAB\n.BBB\n"));
+        assert_eq!(render_first_packing(&regions[0], &variants_list), rendered);
+
+        let impossible = Region {
+            width: 3,
+            height: 3,
+            required: vec![2, 0, 0, 0, 0, 0],
+        };
+        assert_eq!(render_first_packing(&impossible, &variants_list), None);
+    }
+
+    #[test]
+    fn test_best_fill_fraction() {
+        let variants_list = vec![
+            Present::from_lines(&["##"]).rotations_and_flips(),
+            Present::from_lines(&["##", "#."]).rotations_and_flips(),
+        ];
+
+        // Two dominoes tile a 2x2 square exactly
+        let solvable = Region {
+            width: 2,
+            height: 2,
+            required: vec![2, 0],
+        };
+        assert_eq!(best_fill_fraction(&solvable, &variants_list), 1.0);
+
+        // Only one L-tromino fits, covering 3 of 4 cells
+        let impossible = Region {
+            width: 2,
+            height: 2,
+            required: vec![0, 2],
+        };
+        assert_eq!(best_fill_fraction(&impossible, &variants_list), 0.75);
+    }
+
+    #[test]
+    fn test_count_labeled_packings() {
+        let variants_list = vec![
+            Present::from_lines(&["#"]).rotations_and_flips(),
+            Present::from_lines(&["##"]).rotations_and_flips(),
+        ];
+
+        // A monomino and a domino fill a 3x1 strip as A|BB or BB|A
+        let region = Region {
+            width: 3,
+            height: 1,
+            required: vec![1, 1],
+        };
+        assert_eq!(count_packings(&region, &variants_list), 1);
+        assert_eq!(count_labeled_packings(&region, &variants_list), 2);
+
+        // Swapping two copies of the same piece is not a new packing
+        let region = Region {
+            width: 2,
+            height: 1,
+            required: vec![2, 0],
+        };
+        assert_eq!(count_labeled_packings(&region, &variants_list), 1);
+
+        // Shape 256 keeps a label distinct from shape 0 rather than wrapping onto it
+        let variants_list = vec![Present::from_lines(&["#"]).rotations_and_flips(); 257];
+        let mut required = vec![0; 257];
+        required[0] = 1;
+        required[256] = 1;
+        let region = Region {
+            width: 2,
+            height: 1,
+            required,
+        };
+        assert_eq!(count_labeled_packings(&region, &variants_list), 2);
+    }
+
+    #[test]
+    fn test_can_fit_presents_from() {
+        let (shapes, regions) = parse_input(&advent_of_code::template::read_file("examples", DAY));
+        let variants_list: Vec<Vec<Shape>> =
+            shapes.iter().map(|s| s.rotations_and_flips()).collect();
+        let region = &regions[0];
+
+        // Two 7-cell pieces still fit the 4x4 region around a blocked corner
+        let mut grid = Grid::new(region.width, region.height).unwrap();
+        grid.set_cell(0, 0, true);
+        let mut placements = Vec::new();
+        assert!(can_fit_presents_from(
+            &mut grid,
+            &variants_list,
+            &region.required,
+            SolverOptions::default(),
+            &mut placements,
+        ));
+        assert_eq!(placements.len(), 2);
+        assert_eq!(grid.filled_cells, 15);
+        assert!(grid.is_occupied(0, 0));
+
+        let mut grid = Grid::new(region.width, region.height).unwrap();
+        grid.set_cell(1, 1, true);
+        // A blocked center cell leaves as much room, but both pieces can't avoid it
+        assert!(!can_fit_presents_from(
+            &mut grid,
+            &variants_list,
+            &region.required,
+            SolverOptions::default(),
+            &mut Vec::new(),
+        ));
+    }
 }
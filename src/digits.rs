@@ -0,0 +1,53 @@
+/// Decimal digits of `n`, most significant first. Zero is the single digit `[0]`.
+#[must_use]
+pub fn to_digits(mut n: u64) -> Vec<u8> {
+    // u64::MAX has 20 decimal digits
+    let mut digits = Vec::with_capacity(20);
+    loop {
+        digits.push((n % 10) as u8);
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    digits.reverse();
+    digits
+}
+
+/// Values of the ASCII decimal digits in `bytes`, skipping every other byte
+#[must_use]
+pub fn from_ascii_digits(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .filter(|b| b.is_ascii_digit())
+        .map(|b| b - b'0')
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_digits() {
+        assert_eq!(to_digits(0), vec![0]);
+        assert_eq!(to_digits(7), vec![7]);
+        assert_eq!(to_digits(1010), vec![1, 0, 1, 0]);
+        assert_eq!(
+            to_digits(u64::MAX),
+            vec![1, 8, 4, 4, 6, 7, 4, 4, 0, 7, 3, 7, 0, 9, 5, 5, 1, 6, 1, 5]
+        );
+    }
+
+    #[test]
+    fn test_from_ascii_digits() {
+        assert_eq!(from_ascii_digits(b"0"), vec![0]);
+        assert_eq!(from_ascii_digits(b"9"), vec![9]);
+        assert_eq!(from_ascii_digits(b"12 a3\r\n"), vec![1, 2, 3]);
+        assert_eq!(
+            from_ascii_digits(u64::MAX.to_string().as_bytes()),
+            to_digits(u64::MAX)
+        );
+        assert!(from_ascii_digits(b"").is_empty());
+    }
+}
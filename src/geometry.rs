@@ -0,0 +1,219 @@
+/// An integer point on a 2D grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Point2D {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// One of the 8 symmetries of the square grid (rotations and reflections about the origin).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Transform {
+    Identity,
+    /// Quarter turn, mapping (1, 0) to (0, 1)
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Mirror across the y axis, negating x
+    FlipHorizontal,
+    /// Mirror across the main diagonal, swapping x and y
+    Transpose,
+    /// Mirror across the x axis, negating y
+    FlipVertical,
+    /// Mirror across the anti-diagonal
+    AntiTranspose,
+}
+
+impl Transform {
+    /// All 8 transforms: the four rotations, then the four reflections.
+    #[must_use]
+    pub const fn all() -> [Transform; 8] {
+        [
+            Transform::Identity,
+            Transform::Rotate90,
+            Transform::Rotate180,
+            Transform::Rotate270,
+            Transform::FlipHorizontal,
+            Transform::Transpose,
+            Transform::FlipVertical,
+            Transform::AntiTranspose,
+        ]
+    }
+}
+
+/// Apply a grid symmetry to an `(x, y)` coordinate.
+#[must_use]
+pub fn transform_coord((x, y): (i32, i32), t: Transform) -> (i32, i32) {
+    match t {
+        Transform::Identity => (x, y),
+        Transform::Rotate90 => (-y, x),
+        Transform::Rotate180 => (-x, -y),
+        Transform::Rotate270 => (y, -x),
+        Transform::FlipHorizontal => (-x, y),
+        Transform::Transpose => (y, x),
+        Transform::FlipVertical => (x, -y),
+        Transform::AntiTranspose => (-y, -x),
+    }
+}
+
+/// Rasterize a polygon into inclusive x-ranges per row using an even-odd scanline fill.
+///
+/// `vertices` are the polygon's corners in order, with an implicit edge from the last back
+/// to the first. A cell is covered if it lies inside the polygon or on its boundary.
+///
+/// For each row y from the lowest to the highest vertex:
+/// 1. Find all non-horizontal edges crossing the row (half-open in y, so shared vertices
+///    are only counted once)
+/// 2. Sort the crossing points and pair them up to get the interior spans
+/// 3. Add any horizontal edges and vertices lying on the row
+/// 4. Merge overlapping ranges
+///
+/// Returns one `(y, ranges)` entry per row, in ascending y order.
+#[must_use]
+pub fn scanline_fill(vertices: &[Point2D]) -> Vec<(i32, Vec<(i32, i32)>)> {
+    let Some(min_y) = vertices.iter().map(|p| p.y).min() else {
+        return vec![];
+    };
+    let max_y = vertices.iter().map(|p| p.y).max().unwrap();
+    let n = vertices.len();
+
+    (min_y..=max_y)
+        .map(|y| {
+            let mut ranges = Vec::new();
+            let mut crossings = Vec::new();
+
+            for i in 0..n {
+                let p1 = vertices[i];
+                let p2 = vertices[(i + 1) % n];
+
+                if p1.y == y {
+                    ranges.push((p1.x, p1.x));
+                }
+
+                if p1.y == p2.y {
+                    // Horizontal edges at this y-coordinate are part of the boundary
+                    if p1.y == y {
+                        ranges.push((p1.x.min(p2.x), p1.x.max(p2.x)));
+                    }
+                } else if (p1.y <= y && y < p2.y) || (p2.y <= y && y < p1.y) {
+                    let t = f64::from(y - p1.y) / f64::from(p2.y - p1.y);
+                    crossings.push(f64::from(p1.x) + t * f64::from(p2.x - p1.x));
+                }
+            }
+
+            // Pair up crossings; only whole cells between them are inside
+            crossings.sort_unstable_by(f64::total_cmp);
+            for pair in crossings.chunks_exact(2) {
+                let (start, end) = (pair[0].ceil() as i32, pair[1].floor() as i32);
+                if start <= end {
+                    ranges.push((start, end));
+                }
+            }
+
+            merge_ranges(&mut ranges);
+            (y, ranges)
+        })
+        .collect()
+}
+
+/// Merge overlapping ranges in-place, leaving them sorted.
+fn merge_ranges(ranges: &mut Vec<(i32, i32)>) {
+    ranges.sort_unstable();
+    let mut merged: Vec<(i32, i32)> = Vec::with_capacity(ranges.len());
+
+    for &(start, end) in ranges.iter() {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    *ranges = merged;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polygon(coords: &[(i32, i32)]) -> Vec<Point2D> {
+        coords.iter().map(|&(x, y)| Point2D { x, y }).collect()
+    }
+
+    #[test]
+    fn test_square() {
+        let rows = scanline_fill(&polygon(&[(1, 1), (3, 1), (3, 3), (1, 3)]));
+        assert_eq!(
+            rows,
+            vec![(1, vec![(1, 3)]), (2, vec![(1, 3)]), (3, vec![(1, 3)])]
+        );
+    }
+
+    #[test]
+    fn test_l_shape() {
+        let rows = scanline_fill(&polygon(&[(0, 0), (2, 0), (2, 2), (4, 2), (4, 4), (0, 4)]));
+        assert_eq!(
+            rows,
+            vec![
+                (0, vec![(0, 2)]),
+                (1, vec![(0, 2)]),
+                (2, vec![(0, 4)]),
+                (3, vec![(0, 4)]),
+                (4, vec![(0, 4)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_triangle() {
+        let rows = scanline_fill(&polygon(&[(0, 0), (4, 0), (0, 4)]));
+        assert_eq!(
+            rows,
+            vec![
+                (0, vec![(0, 4)]),
+                (1, vec![(0, 3)]),
+                (2, vec![(0, 2)]),
+                (3, vec![(0, 1)]),
+                (4, vec![(0, 0)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(scanline_fill(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_transform_coord() {
+        let mapped: Vec<(i32, i32)> = Transform::all()
+            .into_iter()
+            .map(|t| transform_coord((1, 2), t))
+            .collect();
+        assert_eq!(
+            mapped,
+            vec![
+                (1, 2),
+                (-2, 1),
+                (-1, -2),
+                (2, -1),
+                (-1, 2),
+                (2, 1),
+                (1, -2),
+                (-2, -1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transform_rotations_compose() {
+        let rotate = |c| transform_coord(c, Transform::Rotate90);
+        assert_eq!(
+            rotate(rotate((1, 2))),
+            transform_coord((1, 2), Transform::Rotate180)
+        );
+        assert_eq!(
+            rotate(rotate(rotate((1, 2)))),
+            transform_coord((1, 2), Transform::Rotate270)
+        );
+        assert_eq!(rotate(rotate(rotate(rotate((1, 2))))), (1, 2));
+    }
+}
@@ -0,0 +1,134 @@
+/// Build a minimum spanning forest with Kruskal's algorithm.
+///
+/// `edges` are `(weight, u, v)` triples over nodes `0..num_nodes` and must already be
+/// sorted by weight; ties are taken in the given order. Returns the chosen edges as
+/// `(u, v)` pairs in the order they were added, so the last one is the edge that
+/// completed the tree. The result has `num_nodes - 1` edges only if the graph is connected.
+#[must_use]
+pub fn kruskal(num_nodes: usize, edges: &[(u64, usize, usize)]) -> Vec<(usize, usize)> {
+    let mut sets = DisjointSet::new(num_nodes);
+    let mut tree = Vec::with_capacity(num_nodes.saturating_sub(1));
+
+    for &(_, u, v) in edges {
+        if tree.len() + 1 >= num_nodes {
+            break;
+        }
+        if sets.union(u, v) {
+            tree.push((u, v));
+        }
+    }
+
+    tree
+}
+
+/// Union-find with path compression and union by rank.
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    num_components: usize,
+}
+
+impl DisjointSet {
+    /// `size` singleton sets, one per element `0..size`
+    #[must_use]
+    pub fn new(size: usize) -> Self {
+        DisjointSet {
+            parent: (0..size).collect(),
+            rank: vec![0; size],
+            num_components: size,
+        }
+    }
+
+    /// Representative of the set containing `x`
+    pub fn find(&mut self, x: usize) -> usize {
+        if self.parent[x] != x {
+            self.parent[x] = self.find(self.parent[x]);
+        }
+        self.parent[x]
+    }
+
+    /// Merge the sets containing `x` and `y`, returning false if they were already joined.
+    pub fn union(&mut self, x: usize, y: usize) -> bool {
+        let (root_x, root_y) = (self.find(x), self.find(y));
+        if root_x == root_y {
+            return false;
+        }
+
+        match self.rank[root_x].cmp(&self.rank[root_y]) {
+            std::cmp::Ordering::Less => self.parent[root_x] = root_y,
+            std::cmp::Ordering::Greater => self.parent[root_y] = root_x,
+            std::cmp::Ordering::Equal => {
+                self.parent[root_y] = root_x;
+                self.rank[root_x] += 1;
+            }
+        }
+        self.num_components -= 1;
+        true
+    }
+
+    /// Number of disjoint sets
+    #[must_use]
+    pub fn component_count(&self) -> usize {
+        self.num_components
+    }
+
+    /// Size of every set, in order of each set's representative
+    pub fn component_sizes(&mut self) -> Vec<usize> {
+        let n = self.parent.len();
+        let mut sizes = vec![0; n];
+        for i in 0..n {
+            let root = self.find(i);
+            sizes[root] += 1;
+        }
+        sizes.into_iter().filter(|&size| size > 0).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_mst() {
+        // A square 0-1-2-3 with both diagonals, where the three cheapest edges form a tree
+        let mut edges = vec![
+            (1, 0, 1),
+            (2, 1, 2),
+            (5, 2, 3),
+            (3, 3, 0),
+            (4, 0, 2),
+            (6, 1, 3),
+        ];
+        edges.sort_unstable();
+        assert_eq!(kruskal(4, &edges), vec![(0, 1), (1, 2), (3, 0)]);
+    }
+
+    #[test]
+    fn test_skips_cycle_edges() {
+        let edges = [(1, 0, 1), (1, 1, 2), (1, 0, 2), (2, 2, 3)];
+        assert_eq!(kruskal(4, &edges), vec![(0, 1), (1, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn test_disconnected_graph() {
+        let edges = [(1, 0, 1), (2, 2, 3)];
+        assert_eq!(kruskal(5, &edges), vec![(0, 1), (2, 3)]);
+        assert!(kruskal(1, &[]).is_empty());
+        assert!(kruskal(0, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_disjoint_set() {
+        let mut sets = DisjointSet::new(5);
+        assert_eq!(sets.component_count(), 5);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(3, 1));
+        assert!(!sets.union(0, 3));
+        assert_eq!(sets.find(0), sets.find(3));
+        assert_eq!(sets.component_count(), 3);
+
+        let mut sizes = sets.component_sizes();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![1, 1, 3]);
+    }
+}
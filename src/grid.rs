@@ -0,0 +1,74 @@
+/// The value `peel` writes into removed cells
+pub const EMPTY: u8 = b'.';
+
+/// Repeatedly remove every non-empty cell matching `predicate`, until a round removes
+/// nothing. Returns how many cells each round removed.
+///
+/// Each round tests all cells against the grid as it stood at the start of the round, then
+/// removes the matches together by setting them to [`EMPTY`]. Empty cells are never tested.
+pub fn peel<F: Fn(&[Vec<u8>], usize, usize) -> bool>(
+    grid: &mut [Vec<u8>],
+    predicate: F,
+) -> Vec<usize> {
+    let mut rounds = Vec::new();
+
+    loop {
+        let removed: Vec<(usize, usize)> = grid
+            .iter()
+            .enumerate()
+            .flat_map(|(row, line)| (0..line.len()).map(move |col| (row, col)))
+            .filter(|&(row, col)| grid[row][col] != EMPTY && predicate(grid, row, col))
+            .collect();
+        if removed.is_empty() {
+            break;
+        }
+
+        for &(row, col) in &removed {
+            grid[row][col] = EMPTY;
+        }
+        rounds.push(removed.len());
+    }
+
+    rounds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(rows: &[&str]) -> Vec<Vec<u8>> {
+        rows.iter().map(|row| row.bytes().collect()).collect()
+    }
+
+    /// A cell on the grid's edge or beside an empty cell
+    fn exposed(grid: &[Vec<u8>], row: usize, col: usize) -> bool {
+        row == 0
+            || col == 0
+            || row + 1 == grid.len()
+            || col + 1 == grid[row].len()
+            || [
+                grid[row - 1][col],
+                grid[row + 1][col],
+                grid[row][col - 1],
+                grid[row][col + 1],
+            ]
+            .contains(&EMPTY)
+    }
+
+    #[test]
+    fn test_peel_onion() {
+        let mut grid = parse(&["#####", "#####", "#####", "#####", "#####"]);
+        assert_eq!(peel(&mut grid, exposed), vec![16, 8, 1]);
+        assert!(grid.iter().flatten().all(|&cell| cell == EMPTY));
+    }
+
+    #[test]
+    fn test_peel_stops_when_nothing_matches() {
+        // Only 'a' cells are removable, and the rest stay in place
+        let mut grid = parse(&["ab.", "bab"]);
+        assert_eq!(peel(&mut grid, |g, r, c| g[r][c] == b'a'), vec![2]);
+        assert_eq!(grid, parse(&[".b.", "b.b"]));
+
+        assert!(peel(&mut grid, |_, _, _| false).is_empty());
+    }
+}
@@ -1,3 +1,10 @@
+pub mod digits;
+pub mod geometry;
+pub mod graph;
+pub mod grid;
+pub mod linalg;
+pub mod ranges;
 pub mod template;
+pub mod validate;
 
 // Use this file to add helper functions and additional modules.
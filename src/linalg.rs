@@ -0,0 +1,221 @@
+/// Most free variables the GF(2) solvers will enumerate assignments for
+pub const MAX_FREE_VARIABLES_GF2: usize = 24;
+
+/// A GF(2) system has more than [`MAX_FREE_VARIABLES_GF2`] free variables, too many to
+/// try every assignment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooManyFreeVariables {
+    pub free_vars: usize,
+}
+
+/// Find a least-Hamming-weight solution to `matrix · x = rhs` over GF(2).
+///
+/// `matrix` has one row per equation and one column per variable, and `rhs` holds
+/// one value per equation. Returns `Ok(None)` if the system is inconsistent.
+///
+/// The system is reduced to row echelon form, then every assignment of the free
+/// variables is tried, so the runtime is exponential in the nullity of `matrix`.
+/// Systems with more than [`MAX_FREE_VARIABLES_GF2`] free variables are rejected.
+pub fn min_weight_gf2(
+    matrix: &[Vec<bool>],
+    rhs: &[bool],
+) -> Result<Option<Vec<bool>>, TooManyFreeVariables> {
+    Ok(solutions_gf2(matrix, rhs)?.and_then(|solutions| solutions.min_by_key(|s| weight(s))))
+}
+
+/// Find every least-Hamming-weight solution to `matrix · x = rhs` over GF(2), keeping at
+/// most `limit` of them.
+///
+/// Solutions come back in the order their free-variable assignments are enumerated, and
+/// the result is empty if the system is inconsistent. Like [`min_weight_gf2`], the
+/// runtime is exponential in the nullity of `matrix`, and systems with more than
+/// [`MAX_FREE_VARIABLES_GF2`] free variables are rejected.
+pub fn all_min_weight_gf2(
+    matrix: &[Vec<bool>],
+    rhs: &[bool],
+    limit: usize,
+) -> Result<Vec<Vec<bool>>, TooManyFreeVariables> {
+    let Some(solutions) = solutions_gf2(matrix, rhs)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut best_weight = usize::MAX;
+    let mut best = Vec::new();
+    for solution in solutions {
+        let weight = weight(&solution);
+        if weight < best_weight {
+            best_weight = weight;
+            best.clear();
+        }
+        if weight == best_weight && best.len() < limit {
+            best.push(solution);
+        }
+    }
+
+    Ok(best)
+}
+
+fn weight(solution: &[bool]) -> usize {
+    solution.iter().filter(|&&set| set).count()
+}
+
+/// Every solution to `matrix · x = rhs` over GF(2), or `None` if the system is inconsistent
+fn solutions_gf2(
+    matrix: &[Vec<bool>],
+    rhs: &[bool],
+) -> Result<Option<impl Iterator<Item = Vec<bool>>>, TooManyFreeVariables> {
+    assert_eq!(matrix.len(), rhs.len(), "one rhs value per equation");
+    let num_vars = matrix.first().map_or(0, Vec::len);
+    assert!(
+        matrix.iter().all(|row| row.len() == num_vars),
+        "every equation needs one coefficient per variable"
+    );
+
+    // Augment each row with its rhs value in the last column
+    let mut rows: Vec<Vec<bool>> = matrix
+        .iter()
+        .zip(rhs)
+        .map(|(row, &value)| {
+            let mut row = row.clone();
+            row.push(value);
+            row
+        })
+        .collect();
+
+    let mut pivot_cols = Vec::new();
+    for col in 0..num_vars {
+        let pivot_row = pivot_cols.len();
+        let Some(found) = (pivot_row..rows.len()).find(|&r| rows[r][col]) else {
+            continue;
+        };
+        rows.swap(pivot_row, found);
+
+        // Clear the column from every other row, so each pivot is its row's only pivot
+        let pivot = rows[pivot_row].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            if r != pivot_row && row[col] {
+                row.iter_mut().zip(&pivot).for_each(|(a, &b)| *a ^= b);
+            }
+        }
+        pivot_cols.push(col);
+    }
+
+    // A zeroed-out equation with a set rhs reads 0 = 1
+    if rows[pivot_cols.len()..].iter().any(|row| row[num_vars]) {
+        return Ok(None);
+    }
+
+    let free_vars: Vec<usize> = (0..num_vars)
+        .filter(|col| !pivot_cols.contains(col))
+        .collect();
+    if free_vars.len() > MAX_FREE_VARIABLES_GF2 {
+        return Err(TooManyFreeVariables {
+            free_vars: free_vars.len(),
+        });
+    }
+
+    Ok(Some((0u64..(1 << free_vars.len())).map(
+        move |assignment| {
+            let mut solution = vec![false; num_vars];
+            for (bit, &var) in free_vars.iter().enumerate() {
+                solution[var] = assignment & (1 << bit) != 0;
+            }
+
+            // Each pivot variable is determined by its row's rhs and the free variables
+            for (row, &col) in rows.iter().zip(&pivot_cols) {
+                solution[col] = free_vars.iter().fold(row[num_vars], |value, &var| {
+                    value ^ (row[var] && solution[var])
+                });
+            }
+
+            solution
+        },
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn system(rows: &[&str]) -> Vec<Vec<bool>> {
+        rows.iter()
+            .map(|row| row.bytes().map(|b| b == b'1').collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_unique_solution() {
+        let matrix = system(&["110", "011", "001"]);
+        assert_eq!(
+            min_weight_gf2(&matrix, &[true, false, true]),
+            Ok(Some(vec![false, true, true]))
+        );
+    }
+
+    #[test]
+    fn test_minimum_weight_over_free_variables() {
+        // x0 + x1 + x3 = 1 and x2 + x3 = 1 have many solutions, but only x3 alone has weight 1
+        let matrix = system(&["1101", "0011"]);
+        assert_eq!(
+            min_weight_gf2(&matrix, &[true, true]),
+            Ok(Some(vec![false, false, false, true]))
+        );
+
+        let matrix = system(&["1111"]);
+        let solution = min_weight_gf2(&matrix, &[true]).unwrap().unwrap();
+        assert_eq!(solution.iter().filter(|&&set| set).count(), 1);
+    }
+
+    #[test]
+    fn test_inconsistent() {
+        let matrix = system(&["11", "11"]);
+        assert_eq!(min_weight_gf2(&matrix, &[true, false]), Ok(None));
+        assert_eq!(min_weight_gf2(&system(&["00"]), &[true]), Ok(None));
+    }
+
+    #[test]
+    fn test_zero_rhs() {
+        let matrix = system(&["101", "011"]);
+        assert_eq!(
+            min_weight_gf2(&matrix, &[false, false]),
+            Ok(Some(vec![false; 3]))
+        );
+    }
+
+    #[test]
+    fn test_all_min_weight() {
+        // x0 + x1 = 1 and x1 + x2 = 1 are solved by {x1} alone or by {x0, x2}
+        let matrix = system(&["110", "011"]);
+        assert_eq!(
+            all_min_weight_gf2(&matrix, &[true, true], 10),
+            Ok(vec![vec![false, true, false]])
+        );
+
+        // Any single variable solves x0 + x1 + x2 = 1, but only two are kept
+        let matrix = system(&["111"]);
+        let solutions = all_min_weight_gf2(&matrix, &[true], 2).unwrap();
+        assert_eq!(solutions.len(), 2);
+        assert!(solutions.iter().all(|solution| weight(solution) == 1));
+        assert_ne!(solutions[0], solutions[1]);
+
+        assert_eq!(
+            all_min_weight_gf2(&system(&["00"]), &[true], 10),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "one coefficient per variable")]
+    fn test_ragged_matrix() {
+        let _ = min_weight_gf2(&system(&["110", "01"]), &[true, true]);
+    }
+
+    #[test]
+    fn test_too_many_free_variables() {
+        // One equation over 70 variables leaves 69 of them free
+        let matrix = vec![vec![true; 70]];
+        let error = TooManyFreeVariables { free_vars: 69 };
+        assert_eq!(min_weight_gf2(&matrix, &[true]), Err(error));
+        assert_eq!(all_min_weight_gf2(&matrix, &[true], 10), Err(error));
+    }
+}
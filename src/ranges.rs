@@ -0,0 +1,86 @@
+use std::ops::RangeInclusive;
+
+/// A set of integers stored as sorted, disjoint ranges, for fast membership queries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet {
+    ranges: Vec<RangeInclusive<u64>>,
+}
+
+impl IntervalSet {
+    /// Build the set from ranges that are already sorted and disjoint, e.g. the output of
+    /// a merge pass.
+    #[must_use]
+    pub fn from_merged(ranges: Vec<RangeInclusive<u64>>) -> Self {
+        debug_assert!(
+            ranges.windows(2).all(|w| w[0].end() < w[1].start()),
+            "ranges must be sorted and disjoint"
+        );
+        IntervalSet { ranges }
+    }
+
+    /// Whether any range contains `id`, found by binary search.
+    #[must_use]
+    pub fn contains(&self, id: u64) -> bool {
+        let idx = self.ranges.partition_point(|range| *range.end() < id);
+        self.ranges
+            .get(idx)
+            .is_some_and(|range| *range.start() <= id)
+    }
+
+    /// How many of `ids` are in the set.
+    #[must_use]
+    pub fn covered_count(&self, ids: &[u64]) -> usize {
+        ids.iter().filter(|&&id| self.contains(id)).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contains_boundaries() {
+        let set = IntervalSet::from_merged(vec![3..=5, 10..=20, 22..=22, 30..=u64::MAX]);
+        for id in [3, 4, 5, 10, 20, 22, 30, u64::MAX] {
+            assert!(set.contains(id), "{id}");
+        }
+        for id in [0, 2, 6, 9, 21, 23, 29] {
+            assert!(!set.contains(id), "{id}");
+        }
+        assert_eq!(set.covered_count(&[0, 3, 6, 22, 40]), 3);
+
+        let empty = IntervalSet::from_merged(Vec::new());
+        assert!(!empty.contains(0));
+    }
+
+    #[test]
+    fn test_contains_matches_linear_scan() {
+        // Small xorshift generator, so the ranges are random but reproducible
+        let mut state = 0x9e37_79b9_7f4a_7c15_u64;
+        let mut next = |bound: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % bound
+        };
+
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        for _ in 0..500 {
+            start += 2 + next(50);
+            let end = start + next(30);
+            ranges.push(start..=end);
+            start = end;
+        }
+        let set = IntervalSet::from_merged(ranges.clone());
+
+        for _ in 0..10_000 {
+            let id = next(start + 100);
+            assert_eq!(
+                set.contains(id),
+                ranges.iter().any(|range| range.contains(&id)),
+                "{id}"
+            );
+        }
+    }
+}
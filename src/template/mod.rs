@@ -1,4 +1,4 @@
-use std::{env, fs};
+use std::{env, fs, path::PathBuf};
 
 pub mod aoc_cli;
 pub mod commands;
@@ -15,24 +15,41 @@ pub const ANSI_ITALIC: &str = "\x1b[3m";
 pub const ANSI_BOLD: &str = "\x1b[1m";
 pub const ANSI_RESET: &str = "\x1b[0m";
 
+/// Path of a day's data file, with an optional part suffix like `01-2.txt`.
+fn data_path(folder: &str, day: Day, part: Option<u8>) -> PathBuf {
+    let file_name = match part {
+        Some(part) => format!("{day}-{part}.txt"),
+        None => format!("{day}.txt"),
+    };
+    let cwd = env::current_dir().unwrap();
+    cwd.join("data").join(folder).join(file_name)
+}
+
 /// Helper function that reads a text file to a string.
 #[must_use]
 pub fn read_file(folder: &str, day: Day) -> String {
-    let cwd = env::current_dir().unwrap();
-    let filepath = cwd.join("data").join(folder).join(format!("{day}.txt"));
-    let f = fs::read_to_string(filepath);
+    let f = fs::read_to_string(data_path(folder, day, None));
+    f.expect("could not open input file")
+}
+
+/// Helper function that reads a file to raw bytes, skipping UTF-8 validation.
+#[must_use]
+pub fn read_file_bytes(folder: &str, day: Day) -> Vec<u8> {
+    let f = fs::read(data_path(folder, day, None));
     f.expect("could not open input file")
 }
 
+/// Helper function that checks whether a data file exists, e.g. before reading an example
+/// that may not have been added yet. With a part, looks for the suffixed file like `01-2.txt`.
+#[must_use]
+pub fn example_exists(folder: &str, day: Day, part: Option<u8>) -> bool {
+    data_path(folder, day, part).is_file()
+}
+
 /// Helper function that reads a text file to string, appending a part suffix. E.g. like `01-2.txt`.
 #[must_use]
 pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
-    let cwd = env::current_dir().unwrap();
-    let filepath = cwd
-        .join("data")
-        .join(folder)
-        .join(format!("{day}-{part}.txt"));
-    let f = fs::read_to_string(filepath);
+    let f = fs::read_to_string(data_path(folder, day, Some(part)));
     f.expect("could not open input file")
 }
 
@@ -66,3 +83,17 @@ macro_rules! solution {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example_exists() {
+        assert!(example_exists("examples", crate::day!(1), None));
+        assert!(example_exists("examples", crate::day!(11), Some(2)));
+        assert!(!example_exists("examples", crate::day!(12), Some(2)));
+        assert!(!example_exists("examples", crate::day!(25), None));
+        assert!(!example_exists("missing", crate::day!(1), None));
+    }
+}
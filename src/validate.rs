@@ -0,0 +1,10 @@
+/// Cheap structural check of puzzle input, for parsers whose full construction is expensive.
+///
+/// `validate` checks the input is well-formed without building the parsed structure (e.g.
+/// without precomputing edges or filling grids). It may be stricter than a lenient parser,
+/// rejecting malformed lines that parsing would silently skip.
+pub trait Validate {
+    type Error;
+
+    fn validate(input: &str) -> Result<(), Self::Error>;
+}